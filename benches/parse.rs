@@ -0,0 +1,50 @@
+use cosmetics_parser::parallel::parse_catalog_parallel;
+use cosmetics_parser::*;
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use pest::Parser;
+use std::fs;
+
+/// Repeats the sample catalog until it holds roughly `products` product blocks
+fn large_catalog(products: usize) -> String {
+    let sample = fs::read_to_string("src/input.txt").expect("sample catalog");
+    let per_copy = product_blocks(&sample).len();
+    sample.repeat(products.div_ceil(per_copy))
+}
+
+fn bench_parse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse");
+    group.sample_size(10);
+
+    for products in [1_000, 10_000, 100_000] {
+        let input = large_catalog(products);
+        group.throughput(Throughput::Bytes(input.len() as u64));
+
+        group.bench_with_input(
+            BenchmarkId::new("single_threaded", products),
+            &input,
+            |b, input| {
+                b.iter(|| {
+                    let pairs = Grammar::parse(Rule::products, black_box(input))
+                        .expect("valid catalog")
+                        .next()
+                        .expect("products pair");
+                    pairs
+                        .into_inner()
+                        .map(Product::from_pair)
+                        .collect::<Vec<_>>()
+                })
+            },
+        );
+
+        group.bench_with_input(
+            BenchmarkId::new("parallel", products),
+            &input,
+            |b, input| b.iter(|| parse_catalog_parallel(black_box(input))),
+        );
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_parse);
+criterion_main!(benches);
@@ -0,0 +1,92 @@
+use cosmetics_parser::product_ref::ProductRef;
+use cosmetics_parser::*;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use pest::Parser;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::fs;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Wraps the system allocator to count allocations and allocated bytes
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATED_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// Runs `f` and returns how many allocations and bytes it requested
+fn count_allocations<T>(f: impl FnOnce() -> T) -> (usize, usize) {
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    let bytes = ALLOCATED_BYTES.load(Ordering::Relaxed);
+    black_box(f());
+    (
+        ALLOCATIONS.load(Ordering::Relaxed) - allocations,
+        ALLOCATED_BYTES.load(Ordering::Relaxed) - bytes,
+    )
+}
+
+fn catalog() -> String {
+    fs::read_to_string("src/input.txt")
+        .expect("sample catalog")
+        .repeat(150)
+}
+
+fn owned_products(input: &str) -> Vec<Product> {
+    let pairs = Grammar::parse(Rule::products, input)
+        .expect("valid catalog")
+        .next()
+        .expect("products pair");
+    pairs.into_inner().map(Product::from_pair).collect()
+}
+
+fn borrowed_products(input: &str) -> Vec<ProductRef<'_>> {
+    let pairs = Grammar::parse(Rule::products, input)
+        .expect("valid catalog")
+        .next()
+        .expect("products pair");
+    pairs.into_inner().map(ProductRef::from_pair).collect()
+}
+
+fn bench_product_ref(c: &mut Criterion) {
+    let input = catalog();
+
+    let (owned_allocations, owned_bytes) = count_allocations(|| owned_products(&input));
+    let (borrowed_allocations, borrowed_bytes) = count_allocations(|| borrowed_products(&input));
+    println!(
+        "Product:    {} allocations, {} bytes\nProductRef: {} allocations, {} bytes",
+        owned_allocations, owned_bytes, borrowed_allocations, borrowed_bytes
+    );
+
+    let mut group = c.benchmark_group("product_ref");
+    group.bench_function("owned", |b| {
+        b.iter(|| owned_products(black_box(&input)).len())
+    });
+    group.bench_function("borrowed", |b| {
+        b.iter(|| borrowed_products(black_box(&input)).len())
+    });
+    group.bench_function("borrowed_count_in_stock", |b| {
+        b.iter(|| {
+            borrowed_products(black_box(&input))
+                .iter()
+                .filter(|p| p.availability.is_in_stock())
+                .count()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_product_ref);
+criterion_main!(benches);
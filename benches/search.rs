@@ -0,0 +1,39 @@
+use cosmetics_parser::catalog::Catalog;
+use cosmetics_parser::query::Query;
+use cosmetics_parser::*;
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use std::fs;
+
+/// Repeats the sample catalog until it holds roughly `products` products
+fn large_catalog(products: usize) -> Vec<Product> {
+    let sample = fs::read_to_string("src/input.txt").expect("sample catalog");
+    let per_copy = product_blocks(&sample).len();
+    parse_catalog(&sample.repeat(products.div_ceil(per_copy))).0
+}
+
+fn bench_search(c: &mut Criterion) {
+    let mut group = c.benchmark_group("search");
+    group.sample_size(10);
+
+    for products in [1_000, 10_000, 100_000] {
+        let catalog = Catalog::new(large_catalog(products));
+        let scan =
+            Query::parse("reviews ~ \"hydrating\" or skin_type ~ \"oily\"").expect("valid query");
+
+        group.bench_with_input(
+            BenchmarkId::new("index", products),
+            &catalog,
+            |b, catalog| b.iter(|| catalog.search(black_box("hydrating oily")).len()),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("linear_scan", products),
+            &catalog,
+            |b, catalog| b.iter(|| scan.filter(black_box(catalog.products())).len()),
+        );
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_search);
+criterion_main!(benches);
@@ -0,0 +1,236 @@
+use crate::Product;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// The 26 fragrance allergens that EU cosmetics labels have to name, by their INCI names
+const EU_FRAGRANCE_ALLERGENS: [&str; 26] = [
+    "Amyl Cinnamal",
+    "Amylcinnamyl Alcohol",
+    "Anise Alcohol",
+    "Benzyl Alcohol",
+    "Benzyl Benzoate",
+    "Benzyl Cinnamate",
+    "Benzyl Salicylate",
+    "Butylphenyl Methylpropional",
+    "Cinnamal",
+    "Cinnamyl Alcohol",
+    "Citral",
+    "Citronellol",
+    "Coumarin",
+    "Eugenol",
+    "Evernia Furfuracea Extract",
+    "Evernia Prunastri Extract",
+    "Farnesol",
+    "Geraniol",
+    "Hexyl Cinnamal",
+    "Hydroxycitronellal",
+    "Hydroxyisohexyl 3-Cyclohexene Carboxaldehyde",
+    "Isoeugenol",
+    "Limonene",
+    "Linalool",
+    "Methyl 2-Octynoate",
+    "Alpha-Isomethyl Ionone",
+];
+
+/// A flagged substance with the other names it appears under in ingredient lists
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Substance {
+    pub name: String,
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    /// What kind of substance it is, e.g. `fragrance allergen` or `nut oil`
+    #[serde(default)]
+    pub category: String,
+}
+
+impl Substance {
+    pub fn new(name: &str, aliases: &[&str], category: &str) -> Self {
+        Substance {
+            name: name.to_string(),
+            aliases: aliases.iter().map(|a| a.to_string()).collect(),
+            category: category.to_string(),
+        }
+    }
+
+    /// Whether an ingredient is this substance, or contains one of its names as whole words,
+    /// so `Sweet Almond Oil` is `Almond Oil` but `Almondine` is not
+    pub fn matches(&self, ingredient: &str) -> bool {
+        std::iter::once(&self.name)
+            .chain(&self.aliases)
+            .any(|name| contains_words(ingredient, name))
+    }
+}
+
+/// The lowercase words of a name, so spelling like `Alpha-Isomethyl Ionone` and
+/// `alpha isomethyl ionone` compare equal
+fn words(name: &str) -> Vec<String> {
+    name.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Whether the words of `name` appear one after another in `ingredient`, ignoring letter case
+/// and punctuation
+pub(crate) fn contains_words(ingredient: &str, name: &str) -> bool {
+    let name = words(name);
+    !name.is_empty()
+        && words(ingredient)
+            .windows(name.len())
+            .any(|window| window == name.as_slice())
+}
+
+/// A substance found in a product, with the ingredient it was found in
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Finding {
+    pub substance: String,
+    pub category: String,
+    pub ingredient: String,
+}
+
+/// The flagged substances found in one product
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ProductFindings {
+    pub product_id: Option<u64>,
+    pub product_name: String,
+    pub findings: Vec<Finding>,
+}
+
+/// Which products contain which flagged substances
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct AllergenReport {
+    /// How many products were checked
+    pub checked: usize,
+    /// The products with at least one flagged substance, in catalog order
+    pub products: Vec<ProductFindings>,
+}
+
+/// The allergens and restricted substances ingredients are checked against. Custom lists are
+/// loaded from a TOML or JSON file with a `substances` list, which replaces the built-in one:
+///
+/// ```toml
+/// [[substances]]
+/// name = "Almond Oil"
+/// aliases = ["Prunus Amygdalus Dulcis Oil"]
+/// category = "nut oil"
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct AllergenList {
+    pub substances: Vec<Substance>,
+}
+
+impl AllergenList {
+    /// The EU fragrance allergens, common nut oils and butters, and preservatives whose use in
+    /// cosmetics is restricted
+    pub fn builtin() -> Self {
+        let mut substances: Vec<Substance> = EU_FRAGRANCE_ALLERGENS
+            .iter()
+            .map(|name| Substance::new(name, &[], "fragrance allergen"))
+            .collect();
+        substances.extend([
+            Substance::new(
+                "Almond Oil",
+                &["Prunus Amygdalus Dulcis Oil", "Prunus Dulcis Oil"],
+                "nut oil",
+            ),
+            Substance::new("Argan Oil", &["Argania Spinosa Kernel Oil"], "nut oil"),
+            Substance::new("Coconut Oil", &["Cocos Nucifera Oil"], "nut oil"),
+            Substance::new("Hazelnut Oil", &["Corylus Avellana Seed Oil"], "nut oil"),
+            Substance::new(
+                "Macadamia Oil",
+                &[
+                    "Macadamia Integrifolia Seed Oil",
+                    "Macadamia Ternifolia Seed Oil",
+                ],
+                "nut oil",
+            ),
+            Substance::new("Peanut Oil", &["Arachis Hypogaea Oil"], "nut oil"),
+            Substance::new("Walnut Oil", &["Juglans Regia Seed Oil"], "nut oil"),
+            Substance::new(
+                "Shea Butter",
+                &["Butyrospermum Parkii Butter"],
+                "nut butter",
+            ),
+            Substance::new("Formaldehyde", &[], "restricted substance"),
+            Substance::new("Methylisothiazolinone", &[], "restricted substance"),
+            Substance::new("Methylchloroisothiazolinone", &[], "restricted substance"),
+            Substance::new("Triclosan", &[], "restricted substance"),
+        ]);
+        AllergenList { substances }
+    }
+
+    /// The flagged substances in a comma-separated ingredient list, in the order their
+    /// ingredients are listed. A substance found in several ingredients is reported once
+    pub fn detect(&self, ingredients: &str) -> Vec<Finding> {
+        let mut findings: Vec<Finding> = Vec::new();
+        for ingredient in ingredients.split(',').map(str::trim) {
+            for substance in &self.substances {
+                if substance.matches(ingredient)
+                    && !findings.iter().any(|f| f.substance == substance.name)
+                {
+                    findings.push(Finding {
+                        substance: substance.name.clone(),
+                        category: substance.category.clone(),
+                        ingredient: ingredient.to_string(),
+                    });
+                }
+            }
+        }
+        findings
+    }
+
+    /// Fills in the `allergens` field of every product
+    pub fn annotate(&self, products: &mut [Product]) {
+        for product in products {
+            product.allergens = self
+                .detect(&product.ingredients)
+                .into_iter()
+                .map(|finding| finding.substance)
+                .collect();
+        }
+    }
+
+    /// Checks every product, listing the ones with flagged substances
+    pub fn report(&self, products: &[Product]) -> AllergenReport {
+        AllergenReport {
+            checked: products.len(),
+            products: products
+                .iter()
+                .map(|product| ProductFindings {
+                    product_id: product.product_id,
+                    product_name: product.product_name.clone(),
+                    findings: self.detect(&product.ingredients),
+                })
+                .filter(|p| !p.findings.is_empty())
+                .collect(),
+        }
+    }
+}
+
+impl fmt::Display for AllergenReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for product in &self.products {
+            match product.product_id {
+                Some(id) => writeln!(f, "Product {}: {}", id, product.product_name)?,
+                None => writeln!(f, "{}", product.product_name)?,
+            }
+            for finding in &product.findings {
+                if finding.ingredient.eq_ignore_ascii_case(&finding.substance) {
+                    writeln!(f, "  {} ({})", finding.substance, finding.category)?;
+                } else {
+                    writeln!(
+                        f,
+                        "  {} ({}) in {}",
+                        finding.substance, finding.category, finding.ingredient
+                    )?;
+                }
+            }
+        }
+        writeln!(
+            f,
+            "{} of {} products contain flagged substances",
+            self.products.len(),
+            self.checked
+        )
+    }
+}
@@ -0,0 +1,142 @@
+use crate::Rule;
+use chrono::NaiveDate;
+use pest::iterators::Pair;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::fmt;
+use thiserror::Error;
+
+/// A stock status the grammar accepts but that does not hold a valid value
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum AvailabilityError {
+    #[error("invalid pre-order date `{0}`")]
+    Date(String),
+    #[error("stock quantity `{0}` is too large")]
+    Quantity(String),
+}
+
+/// Whether and how a product can be bought. Serialized with a `status` tag, e.g.
+/// `{"status": "in_stock", "quantity": 12}`
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum Availability {
+    /// In stock, with the quantity on hand when the catalog gives it
+    InStock {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        quantity: Option<u64>,
+    },
+    #[default]
+    OutOfStock,
+    /// Can be ordered now and ships later, until `until` when the catalog gives a date
+    PreOrder {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        until: Option<NaiveDate>,
+    },
+    Discontinued,
+}
+
+impl Availability {
+    /// Reads a `stock_status` pair. `true` stands for in stock and `false` for out of stock
+    pub fn from_pair(pair: Pair<Rule>) -> Result<Self, AvailabilityError> {
+        let Some(status) = pair.into_inner().next() else {
+            return Ok(Availability::default());
+        };
+        let value = |rule: Rule| {
+            status
+                .clone()
+                .into_inner()
+                .find(|p| p.as_rule() == rule)
+                .map(|p| p.as_str())
+        };
+
+        Ok(match status.as_rule() {
+            Rule::in_stock => Availability::InStock {
+                quantity: value(Rule::quantity)
+                    .map(|q| {
+                        q.parse()
+                            .map_err(|_| AvailabilityError::Quantity(q.to_string()))
+                    })
+                    .transpose()?,
+            },
+            Rule::pre_order => Availability::PreOrder {
+                until: value(Rule::date)
+                    .map(|d| {
+                        NaiveDate::parse_from_str(d, "%Y-%m-%d")
+                            .map_err(|_| AvailabilityError::Date(d.to_string()))
+                    })
+                    .transpose()?,
+            },
+            Rule::discontinued => Availability::Discontinued,
+            Rule::boolean if value(Rule::yes).is_some() => Availability::InStock { quantity: None },
+            _ => Availability::OutOfStock,
+        })
+    }
+
+    /// The `status` tag of the JSON output, e.g. `pre_order`
+    pub fn status(&self) -> &'static str {
+        match self {
+            Availability::InStock { .. } => "in_stock",
+            Availability::OutOfStock => "out_of_stock",
+            Availability::PreOrder { .. } => "pre_order",
+            Availability::Discontinued => "discontinued",
+        }
+    }
+
+    pub fn is_in_stock(&self) -> bool {
+        matches!(self, Availability::InStock { .. })
+    }
+
+    /// The quantity on hand, when the product is in stock and the catalog gives it
+    pub fn quantity(&self) -> Option<u64> {
+        match self {
+            Availability::InStock { quantity } => *quantity,
+            _ => None,
+        }
+    }
+}
+
+/// Every `status` tag, in declaration order
+pub const STATUSES: [&str; 4] = ["in_stock", "out_of_stock", "pre_order", "discontinued"];
+
+impl From<bool> for Availability {
+    fn from(in_stock: bool) -> Self {
+        if in_stock {
+            Availability::InStock { quantity: None }
+        } else {
+            Availability::OutOfStock
+        }
+    }
+}
+
+/// Availability as written in JSON: a status object, or `true`/`false` in output written
+/// before availability had more than two states
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Written {
+    Flag(bool),
+    Status(Availability),
+}
+
+/// Deserializes an [`Availability`], accepting the older `true`/`false` form as well
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Availability, D::Error> {
+    Ok(match Written::deserialize(deserializer)? {
+        Written::Flag(in_stock) => Availability::from(in_stock),
+        Written::Status(availability) => availability,
+    })
+}
+
+impl fmt::Display for Availability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Availability::InStock { quantity: None } => write!(f, "in stock"),
+            Availability::InStock {
+                quantity: Some(quantity),
+            } => write!(f, "in stock ({})", quantity),
+            Availability::OutOfStock => write!(f, "out of stock"),
+            Availability::PreOrder { until: None } => write!(f, "pre-order"),
+            Availability::PreOrder { until: Some(date) } => {
+                write!(f, "pre-order until {}", date)
+            }
+            Availability::Discontinued => write!(f, "discontinued"),
+        }
+    }
+}
@@ -0,0 +1,225 @@
+use crate::{parse_catalog_with, ParseError, ParseOptions, Product};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// File extensions picked up when a directory is given as input
+const CATALOG_EXTENSIONS: [&str; 3] = ["md", "markdown", "txt"];
+
+/// An error produced while collecting, reading or writing catalog files
+#[derive(Error, Debug)]
+pub enum BatchError {
+    #[error("invalid glob pattern {pattern}: {source}")]
+    Pattern {
+        pattern: String,
+        source: glob::PatternError,
+    },
+    #[error("no catalog files match {0}")]
+    NoMatch(String),
+    #[error("{path}: {source}")]
+    Io { path: PathBuf, source: io::Error },
+    #[error("failed to serialize {path}: {source}")]
+    Json {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
+}
+
+/// A product tagged with the catalog file it was parsed from
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SourcedProduct {
+    pub source: String,
+    #[serde(flatten)]
+    pub product: Product,
+}
+
+/// The products and errors parsed from a single catalog file
+#[derive(Debug)]
+pub struct FileReport {
+    pub path: PathBuf,
+    pub products: Vec<Product>,
+    pub errors: Vec<ParseError>,
+}
+
+/// Expands files, directories and glob patterns into a sorted list of catalog files without duplicates.
+/// Directories are searched recursively for `.md`, `.markdown` and `.txt` files.
+pub fn collect_inputs(inputs: &[String]) -> Result<Vec<PathBuf>, BatchError> {
+    let mut files = Vec::new();
+
+    for input in inputs {
+        let found = if input.contains(['*', '?', '[']) {
+            let paths = glob::glob(input).map_err(|source| BatchError::Pattern {
+                pattern: input.clone(),
+                source,
+            })?;
+            let mut found = Vec::new();
+            for path in paths.flatten() {
+                if path.is_dir() {
+                    walk_dir(&path, &mut found)?;
+                } else {
+                    found.push(path);
+                }
+            }
+            found
+        } else {
+            let path = PathBuf::from(input);
+            if path.is_dir() {
+                let mut found = Vec::new();
+                walk_dir(&path, &mut found)?;
+                found
+            } else {
+                vec![path]
+            }
+        };
+
+        if found.is_empty() {
+            return Err(BatchError::NoMatch(input.clone()));
+        }
+        files.extend(found);
+    }
+
+    files.sort();
+    files.dedup();
+    Ok(files)
+}
+
+fn walk_dir(dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), BatchError> {
+    let io_err = |source| BatchError::Io {
+        path: dir.to_path_buf(),
+        source,
+    };
+    let mut entries = fs::read_dir(dir)
+        .map_err(io_err)?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(io_err)?;
+    entries.sort();
+
+    for path in entries {
+        if path.is_dir() {
+            walk_dir(&path, files)?;
+        } else if path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| CATALOG_EXTENSIONS.contains(&ext))
+        {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Reads and parses one catalog file
+pub fn parse_path(path: &Path) -> Result<FileReport, BatchError> {
+    parse_path_with(path, &ParseOptions::default())
+}
+
+/// Same as [`parse_path`], reading the file as `options` say. An undetermined number format is
+/// detected separately for each file
+pub fn parse_path_with(path: &Path, options: &ParseOptions) -> Result<FileReport, BatchError> {
+    let input = fs::read_to_string(path).map_err(|source| BatchError::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    let (products, errors) = parse_catalog_with(&input, options);
+
+    Ok(FileReport {
+        path: path.to_path_buf(),
+        products,
+        errors,
+    })
+}
+
+/// Parses every file, keeping going when a single file has errors. A file that cannot be read
+/// gets a report without products whose only error is the I/O error
+pub fn parse_paths(paths: &[PathBuf]) -> Vec<FileReport> {
+    parse_paths_with(paths, &ParseOptions::default())
+}
+
+/// Same as [`parse_paths`], reading every file as `options` say
+pub fn parse_paths_with(paths: &[PathBuf], options: &ParseOptions) -> Vec<FileReport> {
+    paths
+        .iter()
+        .map(|path| match fs::read_to_string(path) {
+            Ok(input) => {
+                let (products, errors) = parse_catalog_with(&input, options);
+                FileReport {
+                    path: path.clone(),
+                    products,
+                    errors,
+                }
+            }
+            Err(source) => FileReport {
+                path: path.clone(),
+                products: Vec::new(),
+                errors: vec![ParseError::Io(source)],
+            },
+        })
+        .collect()
+}
+
+/// Merges all reports into one catalog where each product is tagged with its source file
+pub fn merge_reports(reports: Vec<FileReport>) -> Vec<SourcedProduct> {
+    reports
+        .into_iter()
+        .flat_map(|report| {
+            let source = report.path.display().to_string();
+            report
+                .products
+                .into_iter()
+                .map(move |product| SourcedProduct {
+                    source: source.clone(),
+                    product,
+                })
+        })
+        .collect()
+}
+
+/// Writes one `<file stem>.json` per report into `output_dir`, returning the written paths.
+/// Stems that occur more than once get a numeric suffix so no output is overwritten.
+pub fn write_separate(
+    reports: &[FileReport],
+    output_dir: &Path,
+) -> Result<Vec<PathBuf>, BatchError> {
+    fs::create_dir_all(output_dir).map_err(|source| BatchError::Io {
+        path: output_dir.to_path_buf(),
+        source,
+    })?;
+
+    let mut used = HashSet::new();
+    let mut written = Vec::new();
+
+    for report in reports {
+        let stem = report
+            .path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("catalog");
+        let mut name = format!("{}.json", stem);
+        let mut suffix = 2;
+        while !used.insert(name.clone()) {
+            name = format!("{}-{}.json", stem, suffix);
+            suffix += 1;
+        }
+
+        let output_path = output_dir.join(name);
+        write_json(&output_path, &report.products)?;
+        written.push(output_path);
+    }
+    Ok(written)
+}
+
+/// Serializes `value` as pretty JSON into `path`
+pub fn write_json<T: Serialize + ?Sized>(path: &Path, value: &T) -> Result<(), BatchError> {
+    let json = serde_json::to_string_pretty(value).map_err(|source| BatchError::Json {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    fs::write(path, json).map_err(|source| BatchError::Io {
+        path: path.to_path_buf(),
+        source,
+    })
+}
@@ -0,0 +1,55 @@
+use crate::search::SearchIndex;
+use crate::{parse_catalog_with, ParseError, ParseOptions, Product};
+use serde::Serialize;
+
+/// A product found by a search, with its BM25 score
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct SearchHit<'a> {
+    pub score: f64,
+    pub product: &'a Product,
+}
+
+/// Parsed products together with a full-text index over them
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Catalog {
+    products: Vec<Product>,
+    index: SearchIndex,
+}
+
+impl Catalog {
+    /// Indexes `products`
+    pub fn new(products: Vec<Product>) -> Self {
+        let index = SearchIndex::new(&products);
+        Catalog { products, index }
+    }
+
+    /// Parses catalog text and indexes the products that parsed, returning the failures too
+    pub fn parse(input: &str, options: &ParseOptions) -> (Self, Vec<ParseError>) {
+        let (products, errors) = parse_catalog_with(input, options);
+        (Catalog::new(products), errors)
+    }
+
+    pub fn products(&self) -> &[Product] {
+        &self.products
+    }
+
+    /// Products matching any word of `query`, best first. Words are matched ignoring case and
+    /// diacritics, and a word of three or more letters also matches the words it begins, so
+    /// `hydrat` finds `hydrating`
+    pub fn search(&self, query: &str) -> Vec<SearchHit<'_>> {
+        self.index
+            .search(query)
+            .into_iter()
+            .map(|(index, score)| SearchHit {
+                score,
+                product: &self.products[index],
+            })
+            .collect()
+    }
+}
+
+impl From<Vec<Product>> for Catalog {
+    fn from(products: Vec<Product>) -> Self {
+        Catalog::new(products)
+    }
+}
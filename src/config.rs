@@ -0,0 +1,37 @@
+use serde::de::DeserializeOwned;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// An error produced while reading a settings file, such as an allergen list or a currency file
+#[derive(Error, Debug)]
+pub enum ConfigError {
+    #[error("{path}: {source}")]
+    Io { path: PathBuf, source: io::Error },
+    #[error("invalid TOML: {0}")]
+    Toml(#[from] toml::de::Error),
+    #[error("invalid JSON: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+pub fn from_toml<T: DeserializeOwned>(input: &str) -> Result<T, ConfigError> {
+    Ok(toml::from_str(input)?)
+}
+
+pub fn from_json<T: DeserializeOwned>(input: &str) -> Result<T, ConfigError> {
+    Ok(serde_json::from_str(input)?)
+}
+
+/// Loads a `.json` file as JSON and any other file as TOML
+pub fn load_toml_or_json<T: DeserializeOwned>(path: &Path) -> Result<T, ConfigError> {
+    let input = fs::read_to_string(path).map_err(|source| ConfigError::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    if path.extension().is_some_and(|ext| ext == "json") {
+        from_json(&input)
+    } else {
+        from_toml(&input)
+    }
+}
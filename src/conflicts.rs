@@ -0,0 +1,230 @@
+use crate::allergens::contains_words;
+use crate::Product;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use thiserror::Error;
+
+const RETINOIDS: [&str; 6] = [
+    "Retinol",
+    "Retinal",
+    "Retinyl Palmitate",
+    "Tretinoin",
+    "Adapalene",
+    "Vitamin A",
+];
+/// Exfoliating acids. A bare `BHA` is left out: on ingredient lists it is butylated
+/// hydroxyanisole, a preservative
+const ACIDS: [&str; 7] = [
+    "Glycolic Acid",
+    "Lactic Acid",
+    "Mandelic Acid",
+    "Salicylic Acid",
+    "AHA",
+    "Alpha Hydroxy Acid",
+    "Beta Hydroxy Acid",
+];
+const VITAMIN_C: [&str; 3] = ["Vitamin C", "Ascorbic Acid", "L-Ascorbic Acid"];
+const NIACINAMIDE: [&str; 3] = ["Niacinamide", "Nicotinamide", "Vitamin B3"];
+
+/// An error produced while picking the products to check
+#[derive(Error, Debug)]
+pub enum ConflictError {
+    #[error("no product with id {0} in the catalog")]
+    UnknownProduct(u64),
+}
+
+/// Ingredients that should not be used together: any ingredient matching a name in `first`
+/// conflicts with any ingredient matching a name in `second`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ConflictRule {
+    pub name: String,
+    pub first: Vec<String>,
+    pub second: Vec<String>,
+    /// Why the combination is a problem
+    #[serde(default)]
+    pub reason: String,
+}
+
+impl ConflictRule {
+    pub fn new(name: &str, first: &[&str], second: &[&str], reason: &str) -> Self {
+        let names = |list: &[&str]| list.iter().map(|n| n.to_string()).collect();
+        ConflictRule {
+            name: name.to_string(),
+            first: names(first),
+            second: names(second),
+            reason: reason.to_string(),
+        }
+    }
+}
+
+/// An ingredient of a checked product
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Source {
+    pub product_id: Option<u64>,
+    pub product_name: String,
+    pub ingredient: String,
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} in {}", self.ingredient, self.product_name)
+    }
+}
+
+/// Two ingredients that a rule says should not be combined
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Conflict {
+    pub rule: String,
+    pub reason: String,
+    pub first: Source,
+    pub second: Source,
+}
+
+impl fmt::Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {} + {}", self.rule, self.first, self.second)?;
+        if !self.reason.is_empty() {
+            write!(f, ". {}", self.reason)?;
+        }
+        Ok(())
+    }
+}
+
+/// Known bad ingredient combinations. Custom rules are loaded from a TOML or JSON file with a
+/// `rules` list, which replaces the built-in one:
+///
+/// ```toml
+/// [[rules]]
+/// name = "retinoid + AHA/BHA"
+/// first = ["Retinol", "Tretinoin"]
+/// second = ["Glycolic Acid", "Salicylic Acid"]
+/// reason = "Both exfoliate; together they irritate the skin"
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct ConflictRules {
+    pub rules: Vec<ConflictRule>,
+}
+
+impl ConflictRules {
+    /// Widely cited combinations of actives to keep apart
+    pub fn builtin() -> Self {
+        ConflictRules {
+            rules: vec![
+                ConflictRule::new(
+                    "retinoid + AHA/BHA",
+                    &RETINOIDS,
+                    &ACIDS,
+                    "Both exfoliate, and together they irritate and dry out the skin; use them on \
+                     alternate evenings",
+                ),
+                ConflictRule::new(
+                    "vitamin C + niacinamide",
+                    &VITAMIN_C,
+                    &NIACINAMIDE,
+                    "Niacinamide can make pure vitamin C less effective and cause flushing; apply \
+                     them at different times of day",
+                ),
+                ConflictRule::new(
+                    "retinoid + vitamin C",
+                    &RETINOIDS,
+                    &VITAMIN_C,
+                    "Vitamin C works at a lower pH than retinoids and both can irritate; use \
+                     vitamin C in the morning and retinoids at night",
+                ),
+                ConflictRule::new(
+                    "retinoid + benzoyl peroxide",
+                    &RETINOIDS,
+                    &["Benzoyl Peroxide"],
+                    "Benzoyl peroxide breaks down retinoids, so neither works as well",
+                ),
+                ConflictRule::new(
+                    "vitamin C + AHA/BHA",
+                    &VITAMIN_C,
+                    &ACIDS,
+                    "Acids lower the pH further and together they irritate the skin",
+                ),
+            ],
+        }
+    }
+
+    /// Conflicts between the ingredients of one product
+    pub fn check_product(&self, product: &Product) -> Vec<Conflict> {
+        self.check_routine(&[product])
+    }
+
+    /// Conflicts within every product of a catalog, each product checked on its own
+    pub fn check_catalog(&self, products: &[Product]) -> Vec<Conflict> {
+        products
+            .iter()
+            .flat_map(|product| self.check_product(product))
+            .collect()
+    }
+
+    /// Conflicts between all ingredients of products used together, both within one product
+    /// and across products
+    pub fn check_routine(&self, products: &[&Product]) -> Vec<Conflict> {
+        let sources: Vec<Source> = products
+            .iter()
+            .flat_map(|product| {
+                product
+                    .ingredients
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|ingredient| !ingredient.is_empty())
+                    .map(|ingredient| Source {
+                        product_id: product.product_id,
+                        product_name: product.product_name.clone(),
+                        ingredient: ingredient.to_string(),
+                    })
+            })
+            .collect();
+        let matching = |names: &[String]| -> Vec<&Source> {
+            sources
+                .iter()
+                .filter(|source| {
+                    names
+                        .iter()
+                        .any(|name| contains_words(&source.ingredient, name))
+                })
+                .collect()
+        };
+
+        let mut conflicts = Vec::new();
+        for rule in &self.rules {
+            let second = matching(&rule.second);
+            for first in matching(&rule.first) {
+                for second in &second {
+                    if first == *second {
+                        continue;
+                    }
+                    conflicts.push(Conflict {
+                        rule: rule.name.clone(),
+                        reason: rule.reason.clone(),
+                        first: first.clone(),
+                        second: (*second).clone(),
+                    });
+                }
+            }
+        }
+        conflicts
+    }
+
+    /// Conflicts in a routine made of the catalog products with the given ids
+    pub fn check_ids(
+        &self,
+        products: &[Product],
+        ids: &[u64],
+    ) -> Result<Vec<Conflict>, ConflictError> {
+        let mut routine: Vec<&Product> = Vec::new();
+        for id in ids {
+            let product = products
+                .iter()
+                .find(|p| p.product_id == Some(*id))
+                .ok_or(ConflictError::UnknownProduct(*id))?;
+            if !routine.iter().any(|p| std::ptr::eq(*p, product)) {
+                routine.push(product);
+            }
+        }
+        Ok(self.check_routine(&routine))
+    }
+}
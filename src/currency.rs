@@ -0,0 +1,264 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::OnceLock;
+use thiserror::Error;
+
+/// Active ISO 4217 codes. Codes without an entry in a registry are still accepted, with
+/// their minor units taken from [`iso_decimals`]
+const ISO_4217: &str = "AED AFN ALL AMD ANG AOA ARS AUD AWG AZN BAM BBD BDT BGN BHD BIF BMD BND \
+    BOB BRL BSD BTN BWP BYN BZD CAD CDF CHF CLP CNY COP CRC CUP CVE CZK DJF DKK DOP DZD EGP ERN \
+    ETB EUR FJD FKP GBP GEL GHS GIP GMD GNF GTQ GYD HKD HNL HTG HUF IDR ILS INR IQD IRR ISK JMD \
+    JOD JPY KES KGS KHR KMF KPW KRW KWD KYD KZT LAK LBP LKR LRD LSL LYD MAD MDL MGA MKD MMK MNT \
+    MOP MRU MUR MVR MWK MXN MYR MZN NAD NGN NIO NOK NPR NZD OMR PAB PEN PGK PHP PKR PLN PYG QAR \
+    RON RSD RUB RWF SAR SBD SCR SDG SEK SGD SHP SLE SOS SRD SSP STN SVC SYP SZL THB TJS TMT TND \
+    TOP TRY TTD TWD TZS UAH UGX USD UYU UZS VES VND VUV WST XAF XCD XOF XPF YER ZAR ZMW ZWL";
+
+/// Minor units of an ISO 4217 currency: 0 for the yen and similar, 3 for the dinars, 2 otherwise
+fn iso_decimals(code: &str) -> u32 {
+    match code {
+        "BIF" | "CLP" | "DJF" | "GNF" | "ISK" | "JPY" | "KMF" | "KRW" | "PYG" | "RWF" | "UGX"
+        | "VND" | "VUV" | "XAF" | "XOF" | "XPF" => 0,
+        "BHD" | "IQD" | "JOD" | "KWD" | "LYD" | "OMR" | "TND" => 3,
+        _ => 2,
+    }
+}
+
+fn default_decimals() -> u32 {
+    2
+}
+
+/// An error produced while resolving a currency
+#[derive(Error, Debug)]
+pub enum CurrencyError {
+    #[error("unknown currency `{0}`")]
+    Unknown(String),
+}
+
+/// A currency a price can be given in. Serialized as its code, e.g. `"UAH"`. Deserializing keeps
+/// a code it does not know as [`Currency::Other`], so JSON with custom currencies loads without
+/// their registry
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[serde(from = "String", into = "String")]
+pub enum Currency {
+    Uah,
+    Usd,
+    Eur,
+    Gbp,
+    Pln,
+    Chf,
+    Czk,
+    Jpy,
+    Cny,
+    Inr,
+    Try,
+    Krw,
+    /// Any other ISO 4217 code, or the code of a custom currency
+    Other(String),
+}
+
+/// How a currency is written: its code, the symbols that stand for it, and how many decimal
+/// places its amounts have
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CurrencyInfo {
+    pub code: String,
+    #[serde(default)]
+    pub symbols: Vec<String>,
+    #[serde(default = "default_decimals")]
+    pub decimals: u32,
+}
+
+impl CurrencyInfo {
+    pub fn new(code: &str, symbols: &[&str], decimals: u32) -> Self {
+        CurrencyInfo {
+            code: code.to_string(),
+            symbols: symbols.iter().map(|s| s.to_string()).collect(),
+            decimals,
+        }
+    }
+}
+
+/// Currencies recognized in catalogs on top of the built-in ones, which an empty registry still
+/// knows. Custom currencies are loaded from a TOML or JSON file with a `currencies` list:
+///
+/// ```toml
+/// [[currencies]]
+/// code = "BTC"
+/// symbols = ["₿"]
+/// decimals = 8
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct CurrencyRegistry {
+    pub currencies: Vec<CurrencyInfo>,
+}
+
+/// The built-in currencies, which every registry falls back to
+fn builtin_registry() -> &'static CurrencyRegistry {
+    static BUILTIN: OnceLock<CurrencyRegistry> = OnceLock::new();
+    BUILTIN.get_or_init(CurrencyRegistry::builtin)
+}
+
+/// Finds a currency by code or symbol, later entries first
+fn find<'a>(currencies: &'a [CurrencyInfo], token: &str) -> Option<&'a CurrencyInfo> {
+    currencies
+        .iter()
+        .rev()
+        .find(|c| c.code == token || c.symbols.iter().any(|s| s == token))
+}
+
+impl CurrencyRegistry {
+    /// Common currencies with the symbols they are written with
+    pub fn builtin() -> Self {
+        CurrencyRegistry {
+            currencies: vec![
+                CurrencyInfo::new("UAH", &["₴", "грн", "грн."], 2),
+                CurrencyInfo::new("USD", &["$", "US$"], 2),
+                CurrencyInfo::new("EUR", &["€"], 2),
+                CurrencyInfo::new("GBP", &["£"], 2),
+                CurrencyInfo::new("PLN", &["zł"], 2),
+                CurrencyInfo::new("CHF", &["Fr."], 2),
+                CurrencyInfo::new("CZK", &["Kč"], 2),
+                CurrencyInfo::new("JPY", &["¥", "円"], 0),
+                CurrencyInfo::new("CNY", &["元", "CN¥"], 2),
+                CurrencyInfo::new("INR", &["₹"], 2),
+                CurrencyInfo::new("TRY", &["₺"], 2),
+                CurrencyInfo::new("KRW", &["₩"], 0),
+            ],
+        }
+    }
+
+    pub fn register(&mut self, info: CurrencyInfo) {
+        self.currencies.retain(|c| c.code != info.code);
+        self.currencies.push(info);
+    }
+
+    /// Finds a currency by code or symbol. Registered currencies come first, then the built-in
+    /// ones; any other ISO 4217 code is recognized with its standard number of decimal places
+    pub fn lookup(&self, token: &str) -> Option<CurrencyInfo> {
+        let token = token.trim();
+        if let Some(info) =
+            find(&self.currencies, token).or_else(|| find(&builtin_registry().currencies, token))
+        {
+            return Some(info.clone());
+        }
+
+        ISO_4217
+            .split_whitespace()
+            .find(|code| *code == token)
+            .map(|code| CurrencyInfo::new(code, &[], iso_decimals(code)))
+    }
+
+    /// Resolves a code or symbol such as `UAH`, `₴` or `₿` through this registry
+    pub fn parse(&self, token: &str) -> Result<Currency, CurrencyError> {
+        self.lookup(token)
+            .map(|info| Currency::from_code(&info.code))
+            .ok_or_else(|| CurrencyError::Unknown(token.trim().to_string()))
+    }
+
+    /// Like [`CurrencyRegistry::parse`], but keeps an unknown token as it is written
+    pub fn from_token(&self, token: &str) -> Currency {
+        self.parse(token)
+            .unwrap_or_else(|_| Currency::Other(token.trim().to_string()))
+    }
+
+    /// Resolves a currency that was kept as an unknown token, such as a custom symbol, through
+    /// this registry. Known currencies are returned as they are
+    pub fn resolve(&self, currency: Currency) -> Currency {
+        match currency {
+            Currency::Other(token) => self.from_token(&token),
+            currency => currency,
+        }
+    }
+
+    /// The number of decimal places amounts in `currency` have
+    pub fn decimals(&self, currency: &Currency) -> u32 {
+        self.lookup(currency.code())
+            .map_or(default_decimals(), |info| info.decimals)
+    }
+}
+
+impl Currency {
+    fn from_code(code: &str) -> Self {
+        match code {
+            "UAH" => Currency::Uah,
+            "USD" => Currency::Usd,
+            "EUR" => Currency::Eur,
+            "GBP" => Currency::Gbp,
+            "PLN" => Currency::Pln,
+            "CHF" => Currency::Chf,
+            "CZK" => Currency::Czk,
+            "JPY" => Currency::Jpy,
+            "CNY" => Currency::Cny,
+            "INR" => Currency::Inr,
+            "TRY" => Currency::Try,
+            "KRW" => Currency::Krw,
+            _ => Currency::Other(code.to_string()),
+        }
+    }
+
+    /// Resolves a code or symbol such as `UAH`, `₴` or `грн` among the built-in currencies
+    pub fn parse(token: &str) -> Result<Self, CurrencyError> {
+        builtin_registry().parse(token)
+    }
+
+    /// Like [`Currency::parse`], but keeps an unknown token as it is written
+    pub fn from_token(token: &str) -> Self {
+        builtin_registry().from_token(token)
+    }
+
+    /// The ISO 4217 (or custom) code
+    pub fn code(&self) -> &str {
+        match self {
+            Currency::Uah => "UAH",
+            Currency::Usd => "USD",
+            Currency::Eur => "EUR",
+            Currency::Gbp => "GBP",
+            Currency::Pln => "PLN",
+            Currency::Chf => "CHF",
+            Currency::Czk => "CZK",
+            Currency::Jpy => "JPY",
+            Currency::Cny => "CNY",
+            Currency::Inr => "INR",
+            Currency::Try => "TRY",
+            Currency::Krw => "KRW",
+            Currency::Other(code) => code,
+        }
+    }
+
+    /// The number of decimal places amounts in this currency have, 2 for a custom currency
+    /// unless its registry says otherwise with [`CurrencyRegistry::decimals`]
+    pub fn decimals(&self) -> u32 {
+        builtin_registry().decimals(self)
+    }
+
+    /// Formats an amount with this currency's precision, e.g. `1500 JPY` or `299.99 UAH`
+    pub fn format(&self, amount: f64) -> String {
+        format!("{:.*} {}", self.decimals() as usize, amount, self.code())
+    }
+}
+
+impl FromStr for Currency {
+    type Err = CurrencyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Currency::parse(s)
+    }
+}
+
+impl From<String> for Currency {
+    fn from(value: String) -> Self {
+        Currency::from_token(&value)
+    }
+}
+
+impl From<Currency> for String {
+    fn from(currency: Currency) -> Self {
+        currency.code().to_string()
+    }
+}
+
+impl fmt::Display for Currency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.code())
+    }
+}
@@ -0,0 +1,323 @@
+use crate::exchange::round_to;
+use crate::merge::{merge_product, ConflictPolicy, MergeError, MergeReport};
+use crate::search::tokenize;
+use crate::Product;
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::fmt;
+
+/// How much each part of the similarity counts. The weights of the default add up to 1, so
+/// scores range from 0 to 1
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+pub struct Weights {
+    pub name: f64,
+    pub ingredients: f64,
+    pub price: f64,
+}
+
+impl Default for Weights {
+    fn default() -> Self {
+        Weights {
+            name: 0.5,
+            ingredients: 0.3,
+            price: 0.2,
+        }
+    }
+}
+
+/// When two products count as duplicates
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DedupOptions {
+    /// The lowest similarity score of a duplicate pair
+    pub threshold: f64,
+    pub weights: Weights,
+}
+
+impl Default for DedupOptions {
+    fn default() -> Self {
+        DedupOptions {
+            threshold: 0.75,
+            weights: Weights::default(),
+        }
+    }
+}
+
+/// The weighted parts of a similarity score, which add up to it
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+pub struct Similarity {
+    pub score: f64,
+    /// How alike the names are, as the overlap of their letter pairs
+    pub name: f64,
+    /// How many ingredients the products share, out of all the ingredients of both
+    pub ingredients: f64,
+    /// The cheaper price against the dearer one
+    pub price: f64,
+}
+
+/// A product in a duplicate cluster; `index` is its 0-based position in the catalog
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Member {
+    pub index: usize,
+    pub product_id: Option<u64>,
+    pub product_name: String,
+}
+
+/// Two products of a cluster that are similar enough to be duplicates, by catalog position
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Pair {
+    pub first: usize,
+    pub second: usize,
+    pub similarity: Similarity,
+}
+
+/// Products that are likely the same item. Every member is linked to another one by a pair,
+/// but not every two members need to be similar enough on their own
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Cluster {
+    pub members: Vec<Member>,
+    pub pairs: Vec<Pair>,
+}
+
+impl Cluster {
+    /// The best similarity between two members
+    pub fn score(&self) -> f64 {
+        self.pairs
+            .iter()
+            .map(|pair| pair.similarity.score)
+            .fold(0.0, f64::max)
+    }
+}
+
+/// The likely duplicates in a catalog
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct DedupReport {
+    /// How many products were compared
+    pub checked: usize,
+    /// Clusters of two or more products, in the catalog order of their first member
+    pub clusters: Vec<Cluster>,
+}
+
+/// The letter pairs of a name after folding case and diacritics and dropping punctuation, so
+/// `Face Cream "Moisturizing"` and `face cream moisturizing` have the same pairs
+fn bigrams(name: &str) -> Vec<(char, char)> {
+    let chars: Vec<char> = tokenize(name).join(" ").chars().collect();
+    let mut pairs: Vec<(char, char)> = chars.windows(2).map(|w| (w[0], w[1])).collect();
+    pairs.sort_unstable();
+    pairs
+}
+
+/// The Dice coefficient of the letter pairs of two names, from 0.0 for nothing in common to
+/// 1.0 for names that only differ in case, diacritics or punctuation
+pub fn name_similarity(a: &str, b: &str) -> f64 {
+    let (a, b) = (bigrams(a), bigrams(b));
+    if a.is_empty() || b.is_empty() {
+        return if a == b { 1.0 } else { 0.0 };
+    }
+    // Both lists are sorted, so shared pairs are counted with one walk, each pair as often as
+    // it appears in both
+    let (mut i, mut j, mut shared) = (0, 0, 0);
+    while i < a.len() && j < b.len() {
+        match a[i].cmp(&b[j]) {
+            std::cmp::Ordering::Less => i += 1,
+            std::cmp::Ordering::Greater => j += 1,
+            std::cmp::Ordering::Equal => {
+                shared += 1;
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+    2.0 * shared as f64 / (a.len() + b.len()) as f64
+}
+
+/// The ingredients of a comma-separated list as folded words, so `Aloe Vera` and `aloe-vera`
+/// are the same ingredient
+fn ingredient_set(ingredients: &str) -> BTreeSet<String> {
+    ingredients
+        .split(',')
+        .map(|ingredient| tokenize(ingredient).join(" "))
+        .filter(|ingredient| !ingredient.is_empty())
+        .collect()
+}
+
+/// The Jaccard index of two ingredient lists: shared ingredients out of all ingredients. Two
+/// empty lists are alike
+pub fn ingredient_overlap(a: &str, b: &str) -> f64 {
+    let (a, b) = (ingredient_set(a), ingredient_set(b));
+    let all = a.union(&b).count();
+    if all == 0 {
+        return 1.0;
+    }
+    a.intersection(&b).count() as f64 / all as f64
+}
+
+/// The cheaper price against the dearer one, 1.0 for equal prices
+pub fn price_proximity(a: f64, b: f64) -> f64 {
+    let (low, high) = (a.min(b), a.max(b));
+    if high <= 0.0 {
+        1.0
+    } else {
+        (low / high).max(0.0)
+    }
+}
+
+/// How alike two products are. Prices are compared as they are, so convert a catalog with
+/// mixed currencies first
+pub fn similarity(a: &Product, b: &Product, weights: &Weights) -> Similarity {
+    let name = round_to(
+        weights.name * name_similarity(&a.product_name, &b.product_name),
+        3,
+    );
+    let ingredients = round_to(
+        weights.ingredients * ingredient_overlap(&a.ingredients, &b.ingredients),
+        3,
+    );
+    let price = round_to(weights.price * price_proximity(a.price, b.price), 3);
+    Similarity {
+        score: round_to(name + ingredients + price, 3),
+        name,
+        ingredients,
+        price,
+    }
+}
+
+/// The representative of `i` in a union-find forest, halving paths on the way
+fn find(parents: &mut [usize], mut i: usize) -> usize {
+    while parents[i] != i {
+        parents[i] = parents[parents[i]];
+        i = parents[i];
+    }
+    i
+}
+
+/// Compares every two products and clusters the ones at least `options.threshold` alike.
+/// Similarity is linked transitively: if A is like B and B is like C, all three form one cluster
+pub fn find_duplicates(products: &[Product], options: &DedupOptions) -> DedupReport {
+    let mut parents: Vec<usize> = (0..products.len()).collect();
+    let mut pairs = Vec::new();
+    for (i, a) in products.iter().enumerate() {
+        for (j, b) in products.iter().enumerate().skip(i + 1) {
+            let similarity = similarity(a, b, &options.weights);
+            if similarity.score >= options.threshold {
+                let (root_a, root_b) = (find(&mut parents, i), find(&mut parents, j));
+                // The earlier product stays the root, so clusters keep catalog order
+                parents[root_a.max(root_b)] = root_a.min(root_b);
+                pairs.push(Pair {
+                    first: i,
+                    second: j,
+                    similarity,
+                });
+            }
+        }
+    }
+
+    let mut clusters: Vec<(usize, Cluster)> = Vec::new();
+    for (index, product) in products.iter().enumerate() {
+        let root = find(&mut parents, index);
+        let member = Member {
+            index,
+            product_id: product.product_id,
+            product_name: product.product_name.clone(),
+        };
+        match clusters.iter_mut().find(|(r, _)| *r == root) {
+            Some((_, cluster)) => cluster.members.push(member),
+            None => clusters.push((
+                root,
+                Cluster {
+                    members: vec![member],
+                    pairs: Vec::new(),
+                },
+            )),
+        }
+    }
+    for pair in pairs {
+        let root = find(&mut parents, pair.first);
+        if let Some((_, cluster)) = clusters.iter_mut().find(|(r, _)| *r == root) {
+            cluster.pairs.push(pair);
+        }
+    }
+
+    DedupReport {
+        checked: products.len(),
+        clusters: clusters
+            .into_iter()
+            .map(|(_, cluster)| cluster)
+            .filter(|cluster| cluster.members.len() > 1)
+            .collect(),
+    }
+}
+
+impl DedupReport {
+    /// Merges every cluster into one product, resolving differing fields with `policy` as
+    /// [`merge_catalogs`](crate::merge::merge_catalogs) does. The merged product takes the place
+    /// of the first member, and the conflicts name the catalog positions of the members as
+    /// their catalogs
+    pub fn merge(
+        &self,
+        products: &[Product],
+        policy: ConflictPolicy,
+    ) -> Result<MergeReport, MergeError> {
+        let mut merged: Vec<Option<Product>> = products.iter().cloned().map(Some).collect();
+        let mut conflicts = Vec::new();
+        for cluster in &self.clusters {
+            let occurrences: Vec<(usize, &Product)> = cluster
+                .members
+                .iter()
+                .map(|member| (member.index, &products[member.index]))
+                .collect();
+            let (product, product_conflicts) = merge_product(&occurrences, policy)?;
+            conflicts.extend(product_conflicts);
+            for member in &cluster.members {
+                merged[member.index] = None;
+            }
+            merged[cluster.members[0].index] = Some(product);
+        }
+
+        Ok(MergeReport {
+            products: merged.into_iter().flatten().collect(),
+            conflicts,
+        })
+    }
+}
+
+impl fmt::Display for DedupReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for cluster in &self.clusters {
+            writeln!(
+                f,
+                "{:.3}  {} products:",
+                cluster.score(),
+                cluster.members.len()
+            )?;
+            for member in &cluster.members {
+                match member.product_id {
+                    Some(id) => writeln!(f, "  Product {}: {}", id, member.product_name)?,
+                    None => writeln!(f, "  {}", member.product_name)?,
+                }
+            }
+            for pair in &cluster.pairs {
+                let name = |index: usize| {
+                    let member = cluster.members.iter().find(|m| m.index == index);
+                    member.map_or("", |m| m.product_name.as_str())
+                };
+                let similarity = &pair.similarity;
+                writeln!(
+                    f,
+                    "    {} ~ {}: {:.3} = name {:.3} + ingredients {:.3} + price {:.3}",
+                    name(pair.first),
+                    name(pair.second),
+                    similarity.score,
+                    similarity.name,
+                    similarity.ingredients,
+                    similarity.price
+                )?;
+            }
+        }
+        writeln!(
+            f,
+            "{} cluster(s) of likely duplicates among {} products",
+            self.clusters.len(),
+            self.checked
+        )
+    }
+}
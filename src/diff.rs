@@ -0,0 +1,396 @@
+use crate::availability::Availability;
+use crate::Product;
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::fmt;
+
+/// A field-level change between two revisions of the same product
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Change {
+    Price {
+        old: f64,
+        new: f64,
+    },
+    Availability {
+        old: Availability,
+        new: Availability,
+    },
+    ReviewsAdded {
+        reviews: Vec<String>,
+    },
+    ReviewsRemoved {
+        reviews: Vec<String>,
+    },
+    IngredientsAdded {
+        ingredients: Vec<String>,
+    },
+    IngredientsRemoved {
+        ingredients: Vec<String>,
+    },
+    /// Any other field, with its old and new JSON value
+    Field {
+        field: String,
+        old: Value,
+        new: Value,
+    },
+}
+
+impl Change {
+    /// The product field this change belongs to
+    pub fn field(&self) -> &str {
+        match self {
+            Change::Price { .. } => "price",
+            Change::Availability { .. } => "availability",
+            Change::ReviewsAdded { .. } | Change::ReviewsRemoved { .. } => "reviews",
+            Change::IngredientsAdded { .. } | Change::IngredientsRemoved { .. } => "ingredients",
+            Change::Field { field, .. } => field,
+        }
+    }
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Change::Price { old, new } => {
+                let direction = if new > old { "increased" } else { "decreased" };
+                write!(f, "price {} from {} to {}", direction, old, new)?;
+                if *old != 0.0 {
+                    write!(f, " ({:+.1}%)", (new - old) / old * 100.0)?;
+                }
+                Ok(())
+            }
+            Change::Availability { old, new } => match (old.is_in_stock(), new) {
+                (false, Availability::InStock { .. }) => write!(f, "back in stock"),
+                (true, Availability::OutOfStock) => write!(f, "now out of stock"),
+                _ => write!(f, "availability changed from {} to {}", old, new),
+            },
+            Change::ReviewsAdded { reviews } => {
+                write!(f, "{} new review(s): {}", reviews.len(), reviews.join(" "))
+            }
+            Change::ReviewsRemoved { reviews } => {
+                write!(
+                    f,
+                    "{} review(s) removed: {}",
+                    reviews.len(),
+                    reviews.join(" ")
+                )
+            }
+            Change::IngredientsAdded { ingredients } => {
+                write!(f, "ingredients added: {}", ingredients.join(", "))
+            }
+            Change::IngredientsRemoved { ingredients } => {
+                write!(f, "ingredients removed: {}", ingredients.join(", "))
+            }
+            Change::Field { field, old, new } => {
+                write!(f, "{} changed from {} to {}", field, old, new)
+            }
+        }
+    }
+}
+
+/// All changes of one product present in both revisions
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ProductDiff {
+    pub product_id: Option<u64>,
+    pub product_name: String,
+    /// Positions of the product in the old and new catalogs
+    #[serde(skip)]
+    pub old_index: usize,
+    #[serde(skip)]
+    pub new_index: usize,
+    pub changes: Vec<Change>,
+}
+
+/// The differences between two revisions of a catalog
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct CatalogDiff {
+    pub added: Vec<Product>,
+    pub removed: Vec<Product>,
+    pub changed: Vec<ProductDiff>,
+    /// Positions of the removed products in the old catalog
+    #[serde(skip)]
+    removed_indices: Vec<usize>,
+    /// Positions of every product present in both catalogs, in the old and the new one
+    #[serde(skip)]
+    matched: Vec<(usize, usize)>,
+}
+
+/// Splits a comma-separated ingredient list into trimmed, non-empty names
+fn ingredient_list(ingredients: &str) -> Vec<&str> {
+    ingredients
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+/// Items of `a` that are not in `b`, compared case-insensitively
+fn missing_from(a: &[&str], b: &[&str]) -> Vec<String> {
+    a.iter()
+        .filter(|item| !b.iter().any(|other| other.eq_ignore_ascii_case(item)))
+        .map(|item| item.to_string())
+        .collect()
+}
+
+/// Fields with a change kind of their own, and `source_map`, which tells where a product is
+/// written rather than what it is
+const OWN_KIND: [&str; 5] = [
+    "price",
+    "availability",
+    "ingredients",
+    "reviews",
+    "source_map",
+];
+
+fn product_changes(old: &Product, new: &Product) -> Vec<Change> {
+    let mut changes = Vec::new();
+
+    let (old_json, new_json) = (json!(old), json!(new));
+    let (old_fields, new_fields) = (fields(&old_json), fields(&new_json));
+    let names = old_fields.keys().chain(
+        new_fields
+            .keys()
+            .filter(|name| !old_fields.contains_key(*name)),
+    );
+    for name in names.filter(|name| !OWN_KIND.contains(&name.as_str())) {
+        let old = old_fields.get(name).cloned().unwrap_or(Value::Null);
+        let new = new_fields.get(name).cloned().unwrap_or(Value::Null);
+        if old != new {
+            changes.push(Change::Field {
+                field: name.clone(),
+                old,
+                new,
+            });
+        }
+    }
+
+    if old.price != new.price {
+        changes.push(Change::Price {
+            old: old.price,
+            new: new.price,
+        });
+    }
+    if old.availability != new.availability {
+        changes.push(Change::Availability {
+            old: old.availability,
+            new: new.availability,
+        });
+    }
+
+    let old_ingredients = ingredient_list(&old.ingredients);
+    let new_ingredients = ingredient_list(&new.ingredients);
+    let added = missing_from(&new_ingredients, &old_ingredients);
+    let removed = missing_from(&old_ingredients, &new_ingredients);
+    if added.is_empty() && removed.is_empty() && old.ingredients != new.ingredients {
+        changes.push(Change::Field {
+            field: String::from("ingredients"),
+            old: json!(old.ingredients),
+            new: json!(new.ingredients),
+        });
+    }
+    if !added.is_empty() {
+        changes.push(Change::IngredientsAdded { ingredients: added });
+    }
+    if !removed.is_empty() {
+        changes.push(Change::IngredientsRemoved {
+            ingredients: removed,
+        });
+    }
+
+    let added: Vec<String> = new
+        .reviews
+        .iter()
+        .filter(|r| !old.reviews.contains(r))
+        .cloned()
+        .collect();
+    let removed: Vec<String> = old
+        .reviews
+        .iter()
+        .filter(|r| !new.reviews.contains(r))
+        .cloned()
+        .collect();
+    if added.is_empty() && removed.is_empty() && old.reviews != new.reviews {
+        changes.push(Change::Field {
+            field: String::from("reviews"),
+            old: json!(old.reviews),
+            new: json!(new.reviews),
+        });
+    }
+    if !added.is_empty() {
+        changes.push(Change::ReviewsAdded { reviews: added });
+    }
+    if !removed.is_empty() {
+        changes.push(Change::ReviewsRemoved { reviews: removed });
+    }
+
+    changes
+}
+
+/// The fields of a product's JSON output
+fn fields(product: &Value) -> Map<String, Value> {
+    product.as_object().cloned().unwrap_or_default()
+}
+
+/// Describes a product as `#N name`, or just its name when it has no id
+fn label(product_id: Option<u64>, name: &str) -> String {
+    match product_id {
+        Some(id) => format!("#{} {}", id, name),
+        None => name.to_string(),
+    }
+}
+
+impl CatalogDiff {
+    /// Compares two catalogs, matching products by their `*Product N*` id and
+    /// falling back to a case-insensitive name match for products left unmatched
+    pub fn new(old: &[Product], new: &[Product]) -> Self {
+        let mut matches: Vec<Option<usize>> = vec![None; new.len()];
+        let mut matched_old = vec![false; old.len()];
+
+        let by_id: HashMap<u64, usize> = old
+            .iter()
+            .enumerate()
+            .filter_map(|(i, p)| p.product_id.map(|id| (id, i)))
+            .collect();
+        for (i, product) in new.iter().enumerate() {
+            if let Some(&j) = product.product_id.and_then(|id| by_id.get(&id)) {
+                if !matched_old[j] {
+                    matches[i] = Some(j);
+                    matched_old[j] = true;
+                }
+            }
+        }
+
+        for (i, product) in new.iter().enumerate() {
+            if matches[i].is_some() {
+                continue;
+            }
+            let name = product.product_name.to_lowercase();
+            if let Some(j) = (0..old.len())
+                .find(|&j| !matched_old[j] && old[j].product_name.to_lowercase() == name)
+            {
+                matches[i] = Some(j);
+                matched_old[j] = true;
+            }
+        }
+
+        let mut added = Vec::new();
+        let mut changed = Vec::new();
+        let mut matched = Vec::new();
+        for (i, product) in new.iter().enumerate() {
+            match matches[i] {
+                Some(j) => {
+                    matched.push((j, i));
+                    let changes = product_changes(&old[j], product);
+                    if !changes.is_empty() {
+                        changed.push(ProductDiff {
+                            product_id: product.product_id,
+                            product_name: product.product_name.clone(),
+                            old_index: j,
+                            new_index: i,
+                            changes,
+                        });
+                    }
+                }
+                None => added.push(product.clone()),
+            }
+        }
+
+        let removed_indices: Vec<usize> = (0..old.len()).filter(|&j| !matched_old[j]).collect();
+        CatalogDiff {
+            added,
+            removed: removed_indices.iter().map(|&j| old[j].clone()).collect(),
+            changed,
+            removed_indices,
+            matched,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    /// Expresses the diff as an RFC 6902 JSON patch that turns the old catalog's JSON output
+    /// into the new one. `old` and `new` must be the catalogs this diff was built from. Every
+    /// field of the JSON output of a matched product is compared, including `source_map`, and
+    /// added, replaced or removed depending on whether its key is in the old and the new JSON,
+    /// since optional fields are left out when empty. Removed products are removed from the back
+    /// so earlier indices stay valid, and added products are appended
+    pub fn to_json_patch(&self, old: &[Product], new: &[Product]) -> Value {
+        let mut ops = Vec::new();
+
+        for &(old_index, new_index) in &self.matched {
+            let (Some(old_product), Some(new_product)) = (old.get(old_index), new.get(new_index))
+            else {
+                continue;
+            };
+            let (old_json, new_json) = (json!(old_product), json!(new_product));
+            let (old_fields, new_fields) = (fields(&old_json), fields(&new_json));
+            for (field, value) in &new_fields {
+                let path = format!("/{}/{}", old_index, field);
+                match old_fields.get(field) {
+                    Some(old_value) if old_value == value => {}
+                    Some(_) => ops.push(json!({ "op": "replace", "path": path, "value": value })),
+                    None => ops.push(json!({ "op": "add", "path": path, "value": value })),
+                }
+            }
+            for field in old_fields
+                .keys()
+                .filter(|field| !new_fields.contains_key(*field))
+            {
+                let path = format!("/{}/{}", old_index, field);
+                ops.push(json!({ "op": "remove", "path": path }));
+            }
+        }
+
+        for &index in self.removed_indices.iter().rev() {
+            ops.push(json!({ "op": "remove", "path": format!("/{}", index) }));
+        }
+
+        for product in &self.added {
+            ops.push(json!({ "op": "add", "path": "/-", "value": product }));
+        }
+
+        Value::Array(ops)
+    }
+}
+
+impl fmt::Display for CatalogDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "No changes");
+        }
+
+        if !self.added.is_empty() {
+            writeln!(f, "Added ({}):", self.added.len())?;
+            for product in &self.added {
+                writeln!(
+                    f,
+                    "  + {}",
+                    label(product.product_id, &product.product_name)
+                )?;
+            }
+        }
+        if !self.removed.is_empty() {
+            writeln!(f, "Removed ({}):", self.removed.len())?;
+            for product in &self.removed {
+                writeln!(
+                    f,
+                    "  - {}",
+                    label(product.product_id, &product.product_name)
+                )?;
+            }
+        }
+        if !self.changed.is_empty() {
+            writeln!(f, "Changed ({}):", self.changed.len())?;
+            for diff in &self.changed {
+                writeln!(f, "  ~ {}", label(diff.product_id, &diff.product_name))?;
+                for change in &diff.changes {
+                    writeln!(f, "      {}", change)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,239 @@
+use crate::config::{self, ConfigError};
+use crate::currency::{Currency, CurrencyRegistry};
+use crate::Product;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use thiserror::Error;
+
+/// An error produced while loading exchange rates or converting prices
+#[derive(Error, Debug)]
+pub enum ExchangeError {
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+    #[error("invalid exchange rate {from} -> {to} effective {effective}: {rate}")]
+    InvalidRate {
+        from: String,
+        to: String,
+        rate: f64,
+        effective: NaiveDate,
+    },
+    #[error("unknown currency {0}: the exchange-rate table has no rates for it")]
+    UnknownCurrency(String),
+    #[error("no exchange rate from {from} to {to} effective on or before {date}")]
+    MissingRate {
+        from: String,
+        to: String,
+        date: NaiveDate,
+    },
+    #[error("{0} has no currency to convert its price from")]
+    NoCurrency(String),
+}
+
+/// One unit of `from` costs `rate` units of `to` from the `effective` date onwards
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ExchangeRate {
+    pub from: String,
+    pub to: String,
+    pub rate: f64,
+    pub effective: NaiveDate,
+}
+
+/// A table of exchange rates, loaded from a TOML or JSON file with a `rates` list:
+///
+/// ```toml
+/// [[rates]]
+/// from = "EUR"
+/// to = "UAH"
+/// rate = 44.2
+/// effective = "2024-11-01"
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct ExchangeRates {
+    pub rates: Vec<ExchangeRate>,
+    /// Custom currencies prices may be converted into, for their symbols and decimal places
+    #[serde(skip)]
+    pub currencies: CurrencyRegistry,
+}
+
+/// Rounds half away from zero at `decimals` places. The rounding works on the shortest decimal
+/// form of `value`, so 1.005 rounds to 1.01 even though its binary value is slightly below it
+pub fn round_to(value: f64, decimals: u32) -> f64 {
+    let factor = 10f64.powi(decimals as i32);
+    let shifted: f64 = format!("{}e{}", value, decimals)
+        .parse()
+        .unwrap_or(value * factor);
+    shifted.round() / factor
+}
+
+impl ExchangeRates {
+    /// Builds a table from a list of rates, rejecting rates that are not positive numbers
+    pub fn new(rates: Vec<ExchangeRate>) -> Result<Self, ExchangeError> {
+        for rate in &rates {
+            if !(rate.rate.is_finite() && rate.rate > 0.0) {
+                return Err(ExchangeError::InvalidRate {
+                    from: rate.from.clone(),
+                    to: rate.to.clone(),
+                    rate: rate.rate,
+                    effective: rate.effective,
+                });
+            }
+        }
+        Ok(ExchangeRates {
+            rates,
+            currencies: CurrencyRegistry::default(),
+        })
+    }
+
+    /// Resolves target currencies through `currencies` as well as the built-in ones
+    pub fn with_currencies(mut self, currencies: CurrencyRegistry) -> Self {
+        self.currencies = currencies;
+        self
+    }
+
+    /// Like [`config::from_toml`], checking the rates as [`ExchangeRates::new`] does
+    pub fn from_toml(input: &str) -> Result<Self, ExchangeError> {
+        ExchangeRates::new(config::from_toml::<ExchangeRates>(input)?.rates)
+    }
+
+    /// Like [`config::from_json`], checking the rates as [`ExchangeRates::new`] does
+    pub fn from_json(input: &str) -> Result<Self, ExchangeError> {
+        ExchangeRates::new(config::from_json::<ExchangeRates>(input)?.rates)
+    }
+
+    /// Like [`config::load_toml_or_json`], checking the rates as [`ExchangeRates::new`] does
+    pub fn load(path: &Path) -> Result<Self, ExchangeError> {
+        ExchangeRates::new(config::load_toml_or_json::<ExchangeRates>(path)?.rates)
+    }
+
+    fn knows(&self, currency: &str) -> bool {
+        self.rates
+            .iter()
+            .any(|r| r.from == currency || r.to == currency)
+    }
+
+    /// The latest rate between two currencies effective on `date`, using an entry for the
+    /// opposite direction inverted when that one is newer. Returns the rate with its date
+    fn direct(&self, from: &str, to: &str, date: NaiveDate) -> Option<(f64, NaiveDate)> {
+        self.rates
+            .iter()
+            .filter(|r| r.effective <= date)
+            .filter_map(|r| {
+                if r.from == from && r.to == to {
+                    Some((r.rate, r.effective))
+                } else if r.from == to && r.to == from {
+                    Some((1.0 / r.rate, r.effective))
+                } else {
+                    None
+                }
+            })
+            .max_by_key(|(_, effective)| *effective)
+    }
+
+    /// The rate converting `from` into `to` on `date`. Without a rate between the two,
+    /// conversion goes through one intermediate currency, e.g. USD -> UAH -> EUR
+    pub fn rate(&self, from: &str, to: &str, date: NaiveDate) -> Result<f64, ExchangeError> {
+        if from == to {
+            return Ok(1.0);
+        }
+        for currency in [from, to] {
+            if !self.knows(currency) {
+                return Err(ExchangeError::UnknownCurrency(currency.to_string()));
+            }
+        }
+
+        if let Some((rate, _)) = self.direct(from, to, date) {
+            return Ok(rate);
+        }
+
+        let mut pivots: Vec<&str> = self
+            .rates
+            .iter()
+            .flat_map(|r| [r.from.as_str(), r.to.as_str()])
+            .filter(|c| *c != from && *c != to)
+            .collect();
+        pivots.sort_unstable();
+        pivots.dedup();
+        pivots
+            .into_iter()
+            .find_map(|pivot| {
+                let (first, _) = self.direct(from, pivot, date)?;
+                let (second, _) = self.direct(pivot, to, date)?;
+                Some(first * second)
+            })
+            .ok_or_else(|| ExchangeError::MissingRate {
+                from: from.to_string(),
+                to: to.to_string(),
+                date,
+            })
+    }
+
+    /// Converts an amount and rounds it to the number of decimal places of `to`
+    pub fn convert(
+        &self,
+        amount: f64,
+        from: &str,
+        to: &str,
+        date: NaiveDate,
+    ) -> Result<f64, ExchangeError> {
+        Ok(round_to(
+            amount * self.rate(from, to, date)?,
+            self.currencies.decimals(&self.currencies.from_token(to)),
+        ))
+    }
+
+    /// Converts the price of a product into `to`, given as a code or symbol, updating its
+    /// currency. The original price and the price history are converted at the same date. The
+    /// product is left unchanged when the conversion fails
+    pub fn convert_product(
+        &self,
+        product: &mut Product,
+        to: &str,
+        date: NaiveDate,
+    ) -> Result<(), ExchangeError> {
+        let from = product
+            .currency
+            .as_ref()
+            .ok_or_else(|| ExchangeError::NoCurrency(product.product_name.clone()))?;
+        let to = self.currencies.from_token(to);
+        let convert =
+            |amount: f64, from: &Currency| self.convert(amount, from.code(), to.code(), date);
+
+        let price = convert(product.price, from)?;
+        let original_price = product
+            .original_price
+            .map(|original| convert(original, from))
+            .transpose()?;
+        let mut history = product.price_history.clone();
+        for point in &mut history {
+            point.price = convert(point.price, point.currency.as_ref().unwrap_or(from))?;
+            point.currency = Some(to.clone());
+        }
+
+        product.price = price;
+        product.original_price = original_price;
+        product.price_history = history;
+        product.currency = Some(to);
+        Ok(())
+    }
+
+    /// Converts every product that can be converted into `to`, as [`ExchangeRates::convert_product`]
+    /// does, leaving the others unchanged. Returns the index of each product left unchanged
+    /// together with why it could not be converted
+    pub fn convert_products(
+        &self,
+        products: &mut [Product],
+        to: &str,
+        date: NaiveDate,
+    ) -> Vec<(usize, ExchangeError)> {
+        products
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(i, product)| {
+                self.convert_product(product, to, date)
+                    .err()
+                    .map(|error| (i, error))
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,154 @@
+use crate::Product;
+use serde::{Deserialize, Serialize};
+
+/// An INCI name with the common names catalogs use for it
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Synonym {
+    pub inci: String,
+    #[serde(default)]
+    pub names: Vec<String>,
+}
+
+impl Synonym {
+    pub fn new(inci: &str, names: &[&str]) -> Self {
+        Synonym {
+            inci: inci.to_string(),
+            names: names.iter().map(|n| n.to_string()).collect(),
+        }
+    }
+}
+
+/// An ingredient as the catalog spells it, with its INCI name when the dictionary knows it
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Ingredient {
+    pub name: String,
+    /// `None` for an ingredient the dictionary cannot map
+    pub inci: Option<String>,
+}
+
+/// Maps the names ingredients are listed under to their INCI names. Synonyms are loaded from a
+/// TOML or JSON file with a `synonyms` list and added to the bundled ones:
+///
+/// ```toml
+/// [[synonyms]]
+/// inci = "Simmondsia Chinensis Seed Oil"
+/// names = ["Jojoba Oil", "Jojoba"]
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct InciDictionary {
+    pub synonyms: Vec<Synonym>,
+}
+
+/// A name reduced to its lowercase words, so `Vitamin-E` and `vitamin e` look up the same entry
+fn key(name: &str) -> String {
+    name.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+impl InciDictionary {
+    /// Common names of everyday cosmetic ingredients. Names that already are INCI names, such as
+    /// `Glycerin`, map to themselves
+    pub fn builtin() -> Self {
+        InciDictionary {
+            synonyms: vec![
+                Synonym::new("Aqua", &["Water", "Eau", "Purified Water", "Вода"]),
+                Synonym::new("Glycerin", &["Glycerine", "Glycerol", "Гліцерин"]),
+                Synonym::new("Hyaluronic Acid", &["Гіалуронова кислота"]),
+                Synonym::new("Sodium Hyaluronate", &[]),
+                Synonym::new("Simmondsia Chinensis Seed Oil", &["Jojoba Oil", "Jojoba"]),
+                Synonym::new("Zinc Oxide", &[]),
+                Synonym::new("Titanium Dioxide", &[]),
+                Synonym::new(
+                    "Aloe Barbadensis Leaf Juice",
+                    &["Aloe Vera", "Aloe Vera Juice"],
+                ),
+                Synonym::new("Tocopherol", &["Vitamin E"]),
+                Synonym::new("Retinol", &["Vitamin A"]),
+                Synonym::new("Niacinamide", &["Vitamin B3", "Nicotinamide"]),
+                Synonym::new("Panthenol", &["Vitamin B5", "Provitamin B5", "D-Panthenol"]),
+                Synonym::new("Ascorbic Acid", &["Vitamin C"]),
+                Synonym::new(
+                    "Camellia Sinensis Leaf Extract",
+                    &["Green Tea Extract", "Green Tea"],
+                ),
+                Synonym::new("Squalane", &[]),
+                Synonym::new("Butyrospermum Parkii Butter", &["Shea Butter"]),
+                Synonym::new("Theobroma Cacao Seed Butter", &["Cocoa Butter"]),
+                Synonym::new("Cocos Nucifera Oil", &["Coconut Oil"]),
+                Synonym::new(
+                    "Prunus Amygdalus Dulcis Oil",
+                    &["Almond Oil", "Sweet Almond Oil"],
+                ),
+                Synonym::new("Argania Spinosa Kernel Oil", &["Argan Oil"]),
+                Synonym::new("Rosa Canina Fruit Oil", &["Rosehip Oil", "Rose Hip Oil"]),
+                Synonym::new("Olea Europaea Fruit Oil", &["Olive Oil"]),
+                Synonym::new("Ricinus Communis Seed Oil", &["Castor Oil"]),
+                Synonym::new("Caffeine", &[]),
+                Synonym::new("Cera Alba", &["Beeswax", "Bees Wax"]),
+                Synonym::new("Mentha Piperita Oil", &["Peppermint Oil"]),
+                Synonym::new("Lavandula Angustifolia Oil", &["Lavender Oil"]),
+                Synonym::new("Melaleuca Alternifolia Leaf Oil", &["Tea Tree Oil"]),
+                Synonym::new("Chamomilla Recutita Flower Extract", &["Chamomile Extract"]),
+                Synonym::new("Hamamelis Virginiana Water", &["Witch Hazel"]),
+                Synonym::new("Bentonite", &["Bentonite Clay"]),
+                Synonym::new("Kaolin", &["Kaolin Clay", "White Clay"]),
+                Synonym::new("Charcoal Powder", &["Charcoal", "Activated Charcoal"]),
+                Synonym::new("Salicylic Acid", &[]),
+                // `BHA` is the INCI name of the preservative, not short for beta hydroxy acid
+                Synonym::new("BHA", &["Butylated Hydroxyanisole"]),
+                Synonym::new("Glycolic Acid", &[]),
+                Synonym::new("Lactic Acid", &[]),
+                Synonym::new("Urea", &[]),
+                Synonym::new("Allantoin", &[]),
+            ],
+        }
+    }
+
+    /// Adds a synonym. A name listed under several INCI names maps to the one added last
+    pub fn register(&mut self, synonym: Synonym) {
+        self.synonyms.push(synonym);
+    }
+
+    /// Adds every synonym of `other`, so a loaded file extends the bundled dictionary
+    pub fn extend(&mut self, other: InciDictionary) {
+        for synonym in other.synonyms {
+            self.register(synonym);
+        }
+    }
+
+    /// The INCI name of an ingredient, ignoring letter case and punctuation
+    pub fn lookup(&self, name: &str) -> Option<&str> {
+        let name = key(name);
+        if name.is_empty() {
+            return None;
+        }
+        self.synonyms
+            .iter()
+            .rev()
+            .find(|s| key(&s.inci) == name || s.names.iter().any(|n| key(n) == name))
+            .map(|s| s.inci.as_str())
+    }
+
+    /// Maps every ingredient of a comma-separated list, keeping its original spelling
+    pub fn normalize(&self, ingredients: &str) -> Vec<Ingredient> {
+        ingredients
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(|name| Ingredient {
+                name: name.to_string(),
+                inci: self.lookup(name).map(str::to_string),
+            })
+            .collect()
+    }
+
+    /// Fills in the `inci` field of every product
+    pub fn annotate(&self, products: &mut [Product]) {
+        for product in products {
+            product.inci = self.normalize(&product.ingredients);
+        }
+    }
+}
@@ -0,0 +1,268 @@
+use crate::source_map::SourceSpan;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::sync::OnceLock;
+
+/// The canonical label opening a product, which is followed by the product number instead of `*:`
+const PRODUCT: &str = "Product";
+
+/// The canonical label whose values are rewritten too
+const AVAILABILITY: &str = "Availability";
+
+/// A canonical field label with the other names catalogs write it as
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Label {
+    /// The English label the grammar reads, without the asterisks, e.g. `Skin Type`
+    pub name: String,
+    #[serde(default)]
+    pub aliases: Vec<String>,
+}
+
+impl Label {
+    pub fn new(name: &str, aliases: &[&str]) -> Self {
+        Label {
+            name: name.to_string(),
+            aliases: aliases.iter().map(|a| a.to_string()).collect(),
+        }
+    }
+}
+
+/// Field labels and availability values recognized in catalogs on top of the built-in Ukrainian
+/// ones, which an empty set still knows. Every alias is rewritten to its canonical English form
+/// before parsing. Custom labels are loaded from a TOML or JSON file with a `labels` list and an
+/// optional `values` list, whose names are the words of a stock status such as `in stock`,
+/// `pre-order`, `until` or `true`:
+///
+/// ```toml
+/// [[labels]]
+/// name = "Skin Type"
+/// aliases = ["Typ skóry"]
+///
+/// [[values]]
+/// name = "in stock"
+/// aliases = ["dostępny"]
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct LabelSet {
+    pub labels: Vec<Label>,
+    #[serde(default)]
+    pub values: Vec<Label>,
+}
+
+/// The built-in labels, which every set falls back to
+fn builtin_labels() -> &'static LabelSet {
+    static BUILTIN: OnceLock<LabelSet> = OnceLock::new();
+    BUILTIN.get_or_init(LabelSet::builtin)
+}
+
+impl LabelSet {
+    /// The Ukrainian labels and availability values, plus `Volume` for the size
+    pub fn builtin() -> Self {
+        LabelSet {
+            labels: vec![
+                Label::new(PRODUCT, &["Товар", "Продукт"]),
+                Label::new("Skin Type", &["Тип шкіри"]),
+                Label::new("Size", &["Volume", "Розмір", "Об'єм", "Обʼєм"]),
+                Label::new("Ingredients", &["Склад", "Інгредієнти"]),
+                Label::new("Rating", &["Рейтинг"]),
+                Label::new("Price", &["Ціна"]),
+                Label::new("Price History", &["Історія цін"]),
+                Label::new("User Ratings", &["Оцінки користувачів"]),
+                Label::new("Recommendations", &["Рекомендації"]),
+                Label::new("Reviews", &["Відгуки"]),
+                Label::new(AVAILABILITY, &["Наявність"]),
+            ],
+            values: vec![
+                Label::new("true", &["так"]),
+                Label::new("false", &["ні"]),
+                Label::new("in stock", &["в наявності"]),
+                Label::new("out of stock", &["немає в наявності"]),
+                Label::new("pre-order", &["передзамовлення"]),
+                Label::new("until", &["до"]),
+                Label::new("discontinued", &["знято з виробництва"]),
+            ],
+        }
+    }
+
+    /// Finds the alias `line` starts with, returning the canonical label it stands for and the
+    /// length of the label as written. Labels of this set come before the built-in ones
+    fn find(&self, line: &str) -> Option<(&str, usize)> {
+        let rest = line.strip_prefix('*')?;
+        self.labels
+            .iter()
+            .chain(&builtin_labels().labels)
+            .find_map(|label| {
+                label.aliases.iter().find_map(|alias| {
+                    let after = rest.strip_prefix(alias.as_str())?;
+                    let end = if label.name == PRODUCT { " " } else { "*:" };
+                    after
+                        .starts_with(end)
+                        .then(|| (label.name.as_str(), 1 + alias.len() + end.len()))
+                })
+            })
+    }
+
+    /// Finds the longest availability value alias `text` starts with as a whole word, returning
+    /// the canonical value with the length of the alias
+    fn find_value(&self, text: &str) -> Option<(&str, usize)> {
+        self.values
+            .iter()
+            .chain(&builtin_labels().values)
+            .flat_map(|value| value.aliases.iter().map(move |alias| (value, alias)))
+            .filter(|(_, alias)| {
+                text.strip_prefix(alias.as_str())
+                    .is_some_and(|after| !after.starts_with(char::is_alphanumeric))
+            })
+            .max_by_key(|(_, alias)| alias.len())
+            .map(|(value, alias)| (value.name.as_str(), alias.len()))
+    }
+
+    /// Whether `line` opens a product, with `*Product ` or one of its aliases
+    pub fn is_product_label(&self, line: &str) -> bool {
+        let line = line.trim_start();
+        line.starts_with("*Product ") || self.find(line).is_some_and(|(name, _)| name == PRODUCT)
+    }
+
+    /// Rewrites every label at the start of a line of `text`, and the values of availability
+    /// lines, to their canonical forms. Text without aliases is borrowed as it is
+    pub fn normalize<'a>(&self, text: &'a str) -> Normalized<'a> {
+        let mut rewriter = Rewriter {
+            text,
+            normalized: String::new(),
+            edits: Vec::new(),
+            copied: 0,
+        };
+        let mut offset = 0;
+
+        for (index, line) in text.split_inclusive('\n').enumerate() {
+            let start = offset + line.len() - line.trim_start().len();
+            let mut values = None;
+            if let Some((name, len)) = self.find(&text[start..]) {
+                let label = if name == PRODUCT {
+                    format!("*{} ", name)
+                } else {
+                    format!("*{}*:", name)
+                };
+                rewriter.replace(index + 1, start, len, &label);
+                values = (name == AVAILABILITY).then_some(start + len);
+            } else if text[start..].starts_with("*Availability*:") {
+                values = Some(start + "*Availability*:".len());
+            }
+
+            if let Some(mut at) = values {
+                loop {
+                    at = text.len() - text[at..].trim_start_matches([' ', '\t']).len();
+                    let Some((value, len)) = self.find_value(&text[at..]) else {
+                        break;
+                    };
+                    rewriter.replace(index + 1, at, len, value);
+                    at += len;
+                }
+            }
+            offset += line.len();
+        }
+
+        if rewriter.edits.is_empty() {
+            return Normalized {
+                text: Cow::Borrowed(text),
+                edits: rewriter.edits,
+            };
+        }
+        rewriter.normalized.push_str(&text[rewriter.copied..]);
+        Normalized {
+            text: Cow::Owned(rewriter.normalized),
+            edits: rewriter.edits,
+        }
+    }
+}
+
+/// Builds the normalized text one rewritten alias at a time
+struct Rewriter<'a> {
+    text: &'a str,
+    normalized: String,
+    edits: Vec<LabelEdit>,
+    /// Byte offset of the text copied over so far
+    copied: usize,
+}
+
+impl Rewriter<'_> {
+    /// Replaces the `len` bytes at `start`, on 1-based line `line`, with `replacement`
+    fn replace(&mut self, line: usize, start: usize, len: usize, replacement: &str) {
+        self.normalized.push_str(&self.text[self.copied..start]);
+        self.normalized.push_str(replacement);
+        self.edits.push(LabelEdit {
+            line,
+            end: self.normalized.len(),
+            original_len: len,
+            normalized_len: replacement.len(),
+            original_chars: self.text[start..start + len].chars().count(),
+            normalized_chars: replacement.chars().count(),
+        });
+        self.copied = start + len;
+    }
+}
+
+/// A label or value rewritten by [`LabelSet::normalize`]
+#[derive(Debug, Clone, PartialEq)]
+struct LabelEdit {
+    /// 1-based line of the alias
+    line: usize,
+    /// Byte offset just past the canonical form in the normalized text
+    end: usize,
+    original_len: usize,
+    normalized_len: usize,
+    original_chars: usize,
+    normalized_chars: usize,
+}
+
+/// Catalog text with every label and availability value in its canonical form, remembering what
+/// was rewritten so positions in it map back to the text as written
+#[derive(Debug, Clone, PartialEq)]
+pub struct Normalized<'a> {
+    pub text: Cow<'a, str>,
+    edits: Vec<LabelEdit>,
+}
+
+impl Normalized<'_> {
+    /// The byte offset in the text as written of byte `offset` of the normalized text
+    pub fn original_offset(&self, offset: usize) -> usize {
+        let (original, normalized) = self
+            .edits
+            .iter()
+            .take_while(|edit| edit.end <= offset)
+            .fold((0, 0), |(original, normalized), edit| {
+                (
+                    original + edit.original_len,
+                    normalized + edit.normalized_len,
+                )
+            });
+        offset + original - normalized
+    }
+
+    /// Moves a span of the normalized text back to where it is in the text as written
+    pub fn restore(&self, span: &mut SourceSpan) {
+        let (original, normalized) = self
+            .edits
+            .iter()
+            .filter(|edit| edit.line == span.line && edit.end <= span.start)
+            .fold((0, 0), |(original, normalized), edit| {
+                (
+                    original + edit.original_chars,
+                    normalized + edit.normalized_chars,
+                )
+            });
+        span.column = span.column + original - normalized;
+        span.start = self.original_offset(span.start);
+        span.end = self.original_offset(span.end);
+    }
+
+    /// The slice of `original`, the text as written, that `slice` of the normalized text was
+    /// copied from. Any slice that does not overlap a rewritten alias maps back
+    pub(crate) fn original_str<'o>(&self, original: &'o str, slice: &str) -> &'o str {
+        if slice.is_empty() {
+            return "";
+        }
+        let start = self.original_offset(slice.as_ptr() as usize - self.text.as_ptr() as usize);
+        &original[start..start + slice.len()]
+    }
+}
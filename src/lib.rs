@@ -1,122 +1,414 @@
 use pest::iterators::Pair;
+use pest::Parser;
 use pest_derive::Parser;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use thiserror::Error;
+
+pub mod allergens;
+pub mod availability;
+pub mod batch;
+pub mod catalog;
+pub mod config;
+pub mod conflicts;
+pub mod currency;
+pub mod dedup;
+pub mod diff;
+pub mod exchange;
+pub mod inci;
+pub mod labels;
+pub mod merge;
+pub mod numbers;
+pub mod parallel;
+pub mod pricing;
+pub mod product_ref;
+pub mod quantity;
+pub mod query;
+pub mod reader;
+pub mod recommend;
+pub mod reviews;
+pub mod search;
+pub mod sentiment;
+pub mod source_map;
+pub mod stats;
+
+use availability::Availability;
+use currency::{Currency, CurrencyRegistry};
+use labels::LabelSet;
+use numbers::NumberFormat;
+use pricing::{PricePoint, Pricing};
+use product_ref::ProductRef;
+use quantity::Quantity;
+use reviews::ReviewStars;
+use source_map::SourceMap;
 
 #[derive(Parser)]
 #[grammar = "./grammar.pest"]
 pub struct Grammar;
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Product {
-    product_name: String,
-    skin_type: String,
-    ingredients: String,
-    rating: f64,
-    price: f64,
-    user_ratings: Vec<f64>,
-    recommendations: String,
-    reviews: Vec<String>,
-    availability: bool,
+    #[serde(default)]
+    pub product_id: Option<u64>,
+    pub product_name: String,
+    pub skin_type: String,
+    pub ingredients: String,
+    pub rating: f64,
+    pub price: f64,
+    #[serde(default)]
+    pub currency: Option<Currency>,
+    /// The price before the current discount, when the product is on sale
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub original_price: Option<f64>,
+    /// Earlier prices with the dates they took effect, as listed in the catalog
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub price_history: Vec<PricePoint>,
+    /// The amount of product in the package, such as 50 ml or 200 g
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<Quantity>,
+    pub user_ratings: Vec<f64>,
+    pub recommendations: String,
+    pub reviews: Vec<String>,
+    /// The star ratings given in reviews such as `1. (5/5) "..."`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub review_stars: Vec<ReviewStars>,
+    #[serde(deserialize_with = "availability::deserialize")]
+    pub availability: Availability,
+    /// Lines with labels the parser does not know, such as `*Brand*: CeraVe`, keyed by label
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub extra: BTreeMap<String, String>,
+    /// Flagged substances found in the ingredients, filled in by
+    /// [`allergens::AllergenList::annotate`]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allergens: Vec<String>,
+    /// The ingredients with their INCI names, filled in by [`inci::InciDictionary::annotate`]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub inci: Vec<inci::Ingredient>,
+    /// The sentiment of the reviews, filled in by [`sentiment::Lexicon::annotate`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sentiment: Option<sentiment::SentimentSummary>,
+    /// Where each field was found in the input, when the parser recorded it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_map: Option<SourceMap>,
 }
 
 impl Product {
+    /// Builds a product from a `product` pair, reading its amounts in the format they reveal
     pub fn from_pair(pair: Pair<Rule>) -> Self {
-        let mut product_name = String::new();
-        let mut skin_type = String::new();
-        let mut ingredients = String::new();
-        let mut rating = 0.0;
-        let mut price = 0.0;
-        let mut user_ratings = Vec::new();
-        let mut recommendations = String::new();
-        let mut reviews = Vec::new();
-        let mut availability = false;
-
-        for inner_pair in pair.into_inner() {
-            match inner_pair.as_rule() {
-                Rule::product_name => {
-                    let mut inner = inner_pair.into_inner();
-                    inner.next();
-                    product_name = inner.next().map_or(String::new(), |p: Pair<'_, Rule>| {
-                        p.as_str().trim().to_string()
-                    })
-                }
-                Rule::skin_type => {
-                    let mut inner = inner_pair.into_inner();
-                    inner.next();
-                    skin_type = inner.next().map_or(String::new(), |p: Pair<'_, Rule>| {
-                        p.as_str().trim().to_string()
-                    })
-                }
-                Rule::ingredients => {
-                    let mut inner = inner_pair.into_inner();
-                    inner.next();
-                    ingredients = inner.next().map_or(String::new(), |p: Pair<'_, Rule>| {
-                        p.as_str().trim().to_string()
-                    })
-                }
-                Rule::rating => {
-                    let mut inner = inner_pair.into_inner();
-                    inner.next();
-                    rating = inner.next().map_or(0.0, |p: Pair<'_, Rule>| {
-                        p.as_str().trim().parse::<f64>().unwrap_or(0.0)
-                    });
-                }
-                Rule::price => {
-                    let mut inner = inner_pair.into_inner();
-                    inner.next();
-                    price = inner.next().map_or(0.0, |p: Pair<'_, Rule>| {
-                        p.as_str().trim().parse::<f64>().unwrap_or(0.0)
-                    });
-                }
-                Rule::user_ratings => {
-                    let mut inner = inner_pair.into_inner();
-                    inner.next();
-                    user_ratings = inner
-                        .next()
-                        .map_or(String::new(), |p: Pair<'_, Rule>| {
-                            p.as_str().trim().to_string()
-                        })
-                        .trim_start_matches('[')
-                        .trim_end_matches(']')
-                        .split(',')
-                        .filter_map(|s| s.trim().parse::<f64>().ok())
-                        .collect();
-                }
-                Rule::recommendations => {
-                    let mut inner = inner_pair.into_inner();
-                    inner.next();
-                    recommendations = inner.next().map_or(String::new(), |p: Pair<'_, Rule>| {
-                        p.as_str().trim().to_string()
-                    })
-                }
-                Rule::reviews => {
-                    let mut inner = inner_pair.into_inner();
-                    inner.next();
-                    inner.next();
-                    reviews = inner
-                        .map(|r: Pair<'_, Rule>| r.as_str().trim().to_string())
-                        .collect()
-                }
-                Rule::availability => {
-                    let mut inner = inner_pair.into_inner();
-                    inner.next();
-                    availability = inner
-                        .next()
-                        .map_or(false, |p: Pair<'_, Rule>| p.as_str() == "true")
-                }
-                _ => {}
+        ProductRef::from_pair(pair).into()
+    }
+
+    /// Builds a product from a `product` pair, reading ambiguous amounts such as `1.299` in the
+    /// given format
+    pub fn from_pair_with(pair: Pair<Rule>, numbers: NumberFormat) -> Self {
+        ProductRef::from_pair_with(pair, numbers).into()
+    }
+
+    /// Resolves the currencies of the price and the price history that were kept as unknown
+    /// tokens, such as the symbol of a custom currency, through `currencies`
+    pub fn resolve_currencies(&mut self, currencies: &CurrencyRegistry) {
+        self.currency = self.currency.take().map(|c| currencies.resolve(c));
+        for point in &mut self.price_history {
+            point.currency = point.currency.take().map(|c| currencies.resolve(c));
+        }
+    }
+
+    /// The price of this product with its discount and history
+    pub fn pricing(&self) -> Pricing {
+        Pricing {
+            current: self.price,
+            currency: self.currency.clone(),
+            original: self.original_price,
+            discount_percent: self
+                .original_price
+                .map(|original| pricing::discount_percent(self.price, original)),
+            history: self.price_history.clone(),
+        }
+    }
+
+    /// The price per 100 ml for products sized by volume and per 100 g for products sized by
+    /// mass, rounded to the decimals of the currency. `None` when the size is not known
+    pub fn unit_price(&self) -> Option<f64> {
+        let decimals = self.currency.as_ref().map_or(2, Currency::decimals);
+        self.size?
+            .unit_price(self.price)
+            .map(|price| exchange::round_to(price, decimals))
+    }
+}
+
+/// How catalog text is read
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ParseOptions {
+    /// The decimal separator of amounts; detected from the catalog when `None`
+    pub numbers: Option<NumberFormat>,
+    /// Custom currencies recognized on top of the built-in ones
+    pub currencies: CurrencyRegistry,
+    /// Custom field labels recognized on top of the built-in ones
+    pub labels: LabelSet,
+}
+
+impl ParseOptions {
+    /// Fills in an undetermined number format from the first of `blocks` with an amount that
+    /// reveals it, so one format applies to a whole catalog
+    pub fn detect(&self, blocks: &[ProductBlock]) -> ParseOptions {
+        ParseOptions {
+            numbers: self.numbers.or_else(|| {
+                blocks.iter().find_map(|block| {
+                    let text = self.labels.normalize(block.text);
+                    parse_block(&block.with_text(&text.text), &self.currencies)
+                        .ok()
+                        .and_then(|pair| NumberFormat::detect(&pair))
+                })
+            }),
+            ..self.clone()
+        }
+    }
+}
+
+/// An error produced while parsing a single product block
+#[derive(Error, Debug)]
+pub enum ParseError {
+    #[error("line {line}: {message}")]
+    Syntax { line: usize, message: String },
+    #[error("failed to read input: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// A slice of catalog text holding one product, together with where it starts in the whole input
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ProductBlock<'a> {
+    /// 1-based line the block starts at
+    pub line: usize,
+    /// Byte offset of the block in the whole input
+    pub offset: usize,
+    pub text: &'a str,
+}
+
+impl ProductBlock<'_> {
+    /// The same block with its text replaced, such as by the text with normalized labels
+    pub(crate) fn with_text<'b>(&self, text: &'b str) -> ProductBlock<'b> {
+        ProductBlock {
+            line: self.line,
+            offset: self.offset,
+            text,
+        }
+    }
+}
+
+/// Splits catalog text into product blocks, each starting at a `*Product N*:` line.
+/// Non-blank text before the first product is returned as its own block so it gets reported.
+pub fn product_blocks(input: &str) -> Vec<ProductBlock<'_>> {
+    product_blocks_with(input, &LabelSet::default())
+}
+
+/// Same as [`product_blocks`], also starting blocks at the product labels of `labels`
+pub fn product_blocks_with<'a>(input: &'a str, labels: &LabelSet) -> Vec<ProductBlock<'a>> {
+    let mut blocks = Vec::new();
+    let mut start = 0;
+    let mut start_line = 1;
+    let mut offset = 0;
+
+    for (index, line) in input.split_inclusive('\n').enumerate() {
+        if offset > 0 && labels.is_product_label(line) {
+            let text = &input[start..offset];
+            if !text.trim().is_empty() {
+                blocks.push(ProductBlock {
+                    line: start_line,
+                    offset: start,
+                    text,
+                });
             }
+            start = offset;
+            start_line = index + 1;
         }
-        Product {
-            product_name,
-            skin_type,
-            ingredients,
-            rating,
-            price,
-            user_ratings,
-            recommendations,
-            reviews,
-            availability,
+        offset += line.len();
+    }
+
+    let text = &input[start..];
+    if !text.trim().is_empty() {
+        blocks.push(ProductBlock {
+            line: start_line,
+            offset: start,
+            text,
+        });
+    }
+    blocks
+}
+
+/// Parses one product block and checks that nothing but whitespace follows the product and that
+/// its currencies are in `currencies`
+pub(crate) fn parse_block<'a>(
+    block: &ProductBlock<'a>,
+    currencies: &CurrencyRegistry,
+) -> Result<Pair<'a, Rule>, ParseError> {
+    let pair = Grammar::parse(Rule::product, block.text)
+        .map_err(|e| {
+            let (row, _) = match e.line_col {
+                pest::error::LineColLocation::Pos(pos) => pos,
+                pest::error::LineColLocation::Span(pos, _) => pos,
+            };
+            ParseError::Syntax {
+                line: block.line + row - 1,
+                message: e.variant.message().to_string(),
+            }
+        })?
+        .next()
+        .ok_or_else(|| ParseError::Syntax {
+            line: block.line,
+            message: String::from("empty product block"),
+        })?;
+
+    let end = pair.as_span().end();
+    let parsed_lines = block.text[..end].matches('\n').count();
+    if let Some((row, text)) = block.text[end..]
+        .lines()
+        .enumerate()
+        .find(|(_, text)| !text.trim().is_empty())
+    {
+        return Err(ParseError::Syntax {
+            line: block.line + parsed_lines + row,
+            message: format!("unexpected line: {}", text.trim()),
+        });
+    }
+    if let Err(ParseError::Syntax { line, message }) = check_product_with(&pair, currencies) {
+        return Err(ParseError::Syntax {
+            line: block.line + line - 1,
+            message,
+        });
+    }
+
+    Ok(pair)
+}
+
+/// Checks what the grammar leaves open in a parsed `product` pair: that its currencies are
+/// built-in ones, that its dates and stock quantity are valid, that a discount matches its
+/// prices, that the size is given at most once and that lists of numbers read the same in the
+/// product's number format. Amounts are read in the format the product reveals. Lines are relative to the text the pair was parsed from
+pub fn check_product(pair: &Pair<Rule>) -> Result<(), ParseError> {
+    check_product_with(pair, &CurrencyRegistry::default())
+}
+
+/// Same as [`check_product`], also accepting the custom currencies of `currencies`
+pub fn check_product_with(
+    pair: &Pair<Rule>,
+    currencies: &CurrencyRegistry,
+) -> Result<(), ParseError> {
+    let error = |at: &Pair<Rule>, message: String| ParseError::Syntax {
+        line: at.line_col().0,
+        message,
+    };
+    let numbers = NumberFormat::detect(pair).unwrap_or_default();
+    let descendants = || pair.clone().into_inner().flatten();
+
+    if let Some(size) = descendants().filter(|p| p.as_rule() == Rule::size).nth(1) {
+        return Err(error(&size, String::from("size given more than once")));
+    }
+
+    if let Some(currency) = descendants()
+        .find(|p| p.as_rule() == Rule::currency && currencies.lookup(p.as_str()).is_none())
+    {
+        return Err(error(
+            &currency,
+            format!("unknown currency `{}`", currency.as_str()),
+        ));
+    }
+    for inner in descendants() {
+        match inner.as_rule() {
+            Rule::stock_status => {
+                Availability::from_pair(inner.clone()).map_err(|e| error(&inner, e.to_string()))?;
+            }
+            Rule::price => {
+                pricing::check_discount(&inner, numbers, currencies)
+                    .map_err(|e| error(&inner, e.to_string()))?;
+            }
+            Rule::price_point => {
+                PricePoint::from_pair(inner.clone(), numbers)
+                    .map_err(|e| error(&inner, e.to_string()))?;
+            }
+            _ => {}
+        }
+    }
+    check_number_lists(pair, numbers, 1)
+}
+
+/// Checks that no list of numbers in a parsed `product` pair reads differently with decimal
+/// commas: in such a catalog `[4,5]` could be one rating or two, so the numbers of a
+/// comma-separated list must be followed by a space. Lines count from `first_line`, the line the
+/// pair's text starts at
+pub(crate) fn check_number_lists(
+    pair: &Pair<Rule>,
+    numbers: NumberFormat,
+    first_line: usize,
+) -> Result<(), ParseError> {
+    if numbers != NumberFormat::DecimalComma {
+        return Ok(());
+    }
+    let ambiguous = pair.clone().into_inner().flatten().find(|p| {
+        p.as_rule() == Rule::number_list
+            && p.clone().into_inner().all(|n| n.as_rule() == Rule::number)
+            && p.as_str()
+                .as_bytes()
+                .windows(2)
+                .any(|w| w[0] == b',' && w[1].is_ascii_digit())
+    });
+    match ambiguous {
+        Some(list) => Err(ParseError::Syntax {
+            line: first_line + list.line_col().0 - 1,
+            message: format!(
+                "`{}` is ambiguous with decimal commas, write `[4, 5]` for separate numbers or `[4,5; 3,5]` for decimals",
+                list.as_str()
+            ),
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Parses one product block, recording where each field was found in the whole input
+pub fn parse_product(block: &ProductBlock<'_>) -> Result<Product, ParseError> {
+    parse_product_with(block, &ParseOptions::default())
+}
+
+/// Same as [`parse_product`], with the number format taken from `options` when it is set and
+/// the custom currencies and labels of `options`
+pub fn parse_product_with(
+    block: &ProductBlock<'_>,
+    options: &ParseOptions,
+) -> Result<Product, ParseError> {
+    let text = options.labels.normalize(block.text);
+    let pair = parse_block(&block.with_text(&text.text), &options.currencies)?;
+    let mut source_map = SourceMap::from_pair(&pair);
+    source_map.restore(&text);
+    source_map.shift(block.offset, block.line - 1);
+
+    let numbers = options
+        .numbers
+        .or_else(|| NumberFormat::detect(&pair))
+        .unwrap_or_default();
+    check_number_lists(&pair, numbers, block.line)?;
+    let mut product = Product::from_pair_with(pair, numbers);
+    product.resolve_currencies(&options.currencies);
+    product.source_map = Some(source_map);
+    Ok(product)
+}
+
+/// Parses every product block of a catalog, collecting failures instead of stopping at the first one
+pub fn parse_catalog(input: &str) -> (Vec<Product>, Vec<ParseError>) {
+    parse_catalog_with(input, &ParseOptions::default())
+}
+
+/// Same as [`parse_catalog`], reading the catalog as `options` say. An undetermined number
+/// format is detected once for the whole catalog
+pub fn parse_catalog_with(input: &str, options: &ParseOptions) -> (Vec<Product>, Vec<ParseError>) {
+    let mut products = Vec::new();
+    let mut errors = Vec::new();
+
+    let blocks = product_blocks_with(input, &options.labels);
+    let options = options.detect(&blocks);
+    for block in blocks {
+        match parse_product_with(&block, &options) {
+            Ok(product) => products.push(product),
+            Err(e) => errors.push(e),
         }
     }
+    (products, errors)
 }
@@ -1,9 +1,32 @@
 use anyhow::{anyhow, Result};
+use chrono::{Local, NaiveDate};
+use cosmetics_parser::allergens::AllergenList;
+use cosmetics_parser::batch::{self, FileReport};
+use cosmetics_parser::catalog::Catalog;
+use cosmetics_parser::config::load_toml_or_json;
+use cosmetics_parser::conflicts::ConflictRules;
+use cosmetics_parser::currency::CurrencyRegistry;
+use cosmetics_parser::dedup::{find_duplicates, DedupOptions};
+use cosmetics_parser::diff::CatalogDiff;
+use cosmetics_parser::exchange::ExchangeRates;
+use cosmetics_parser::inci::InciDictionary;
+use cosmetics_parser::labels::LabelSet;
+use cosmetics_parser::merge::{merge_catalogs, ConflictPolicy};
+use cosmetics_parser::numbers::NumberFormat;
+use cosmetics_parser::parallel;
+use cosmetics_parser::query::{project, sort_products, Field, Query, SortOrder};
+use cosmetics_parser::reader::ProductReader;
+use cosmetics_parser::recommend::{recommend, Preferences};
+use cosmetics_parser::reviews;
+use cosmetics_parser::sentiment::Lexicon;
+use cosmetics_parser::source_map::SourceMap;
+use cosmetics_parser::stats::CatalogStats;
 use cosmetics_parser::*;
 use pest::Parser;
 use std::env;
-use std::fs::File;
-use std::io::{Read, Write};
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::path::Path;
 
 fn main() -> Result<()> {
     //_ = parse_file(String::from("./src/input.txt"), String::from("./src/output.json"));
@@ -17,6 +40,30 @@ fn main() -> Result<()> {
     match args[1].as_str() {
         "--help" => print_info(),
         "--credits" => println!("Cosmetics Parser by Julia Skip"),
+        "batch" => {
+            let rest = positional(&args[2..]);
+            if rest.len() < 2 {
+                eprintln!("Error: batch needs an output path and at least one input.");
+                print_info();
+                return Ok(());
+            }
+            parse_batch(
+                &rest[1..],
+                &rest[0],
+                has_flag(&args, "--split"),
+                &Export::from_args(&args)?,
+            )?;
+        }
+        "query" => run_query(&args[2..])?,
+        "allergens" => run_allergens(&args[2..])?,
+        "reviews" => run_reviews(&args[2..])?,
+        "conflicts" => run_conflicts(&args[2..])?,
+        "recommend" => run_recommend(&args[2..])?,
+        "search" => run_search(&args[2..])?,
+        "stats" => run_stats(&args[2..])?,
+        "diff" => run_diff(&args[2..])?,
+        "merge" => run_merge(&args[2..])?,
+        "dedup" => run_dedup(&args[2..])?,
         _ => {
             if args.len() < 3 {
                 eprintln!("Error: Missing output file path.");
@@ -25,30 +72,346 @@ fn main() -> Result<()> {
             }
             let input_path = &args[1];
             let output_path = &args[2];
-            parse_file(input_path, output_path)?;
+            let export = Export::from_args(&args)?;
+            if input_path == "-" || has_flag(&args, "--stream") {
+                parse_stream(input_path, output_path, &export)?;
+            } else if has_flag(&args, "--parallel") {
+                parse_file_parallel(input_path, output_path, &export)?;
+            } else {
+                parse_file(input_path, output_path, &export)?;
+            }
         }
     }
 
     Ok(())
 }
 
+/// Flags that take the following argument as their value
+const VALUE_FLAGS: [&str; 17] = [
+    "--top",
+    "--lexicon",
+    "--threshold",
+    "--merge",
+    "--policy",
+    "--allergens",
+    "--synonyms",
+    "--rules",
+    "--ids",
+    "--budget",
+    "--exclude",
+    "--currency",
+    "--rates",
+    "--date",
+    "--currencies",
+    "--numbers",
+    "--labels",
+];
+
+fn has_flag(args: &[String], flag: &str) -> bool {
+    args.iter().any(|a| a == flag)
+}
+
+/// The argument following `flag`, if the flag is present
+fn flag_value<'a>(args: &'a [String], flag: &str) -> Result<Option<&'a str>> {
+    match args.iter().position(|a| a == flag) {
+        Some(index) => args
+            .get(index + 1)
+            .map(|value| Some(value.as_str()))
+            .ok_or_else(|| anyhow!("{} needs a value", flag)),
+        None => Ok(None),
+    }
+}
+
+/// Arguments that are not `--flags` or the values of flags
+fn positional(args: &[String]) -> Vec<String> {
+    let mut rest = Vec::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if VALUE_FLAGS.contains(&arg.as_str()) {
+            iter.next();
+        } else if !arg.starts_with("--") {
+            rest.push(arg.clone());
+        }
+    }
+    rest
+}
+
+/// Converts prices into one currency with `--currency <code>`
+struct Conversion {
+    rates: ExchangeRates,
+    currency: String,
+    date: NaiveDate,
+}
+
+impl Conversion {
+    /// Reads `--currency`, plus `--rates <file>` (default `rates.toml`) and `--date <YYYY-MM-DD>`
+    /// (default today) that pick the exchange rates
+    fn from_args(args: &[String]) -> Result<Option<Self>> {
+        let Some(currency) = flag_value(args, "--currency")? else {
+            return Ok(None);
+        };
+        let currencies = currency_registry(args)?;
+        let rates = ExchangeRates::load(Path::new(
+            flag_value(args, "--rates")?.unwrap_or("rates.toml"),
+        ))?;
+        let date = match flag_value(args, "--date")? {
+            Some(date) => date
+                .parse()
+                .map_err(|e| anyhow!("Invalid --date {}: {}", date, e))?,
+            None => Local::now().date_naive(),
+        };
+
+        Ok(Some(Conversion {
+            currency: currencies
+                .parse(currency)
+                .or_else(|_| currencies.parse(&currency.to_uppercase()))?
+                .to_string(),
+            rates: rates.with_currencies(currencies),
+            date,
+        }))
+    }
+
+    /// Converts every product that can be converted, warning about the ones left in their own
+    /// currency
+    fn convert(&self, products: &mut [Product]) {
+        for (i, error) in self
+            .rates
+            .convert_products(products, &self.currency, self.date)
+        {
+            eprintln!(
+                "warning: {} not converted to {}: {}",
+                products[i].product_name, self.currency, error
+            );
+        }
+    }
+}
+
+/// Reads `--currencies <file>` with custom currencies; without it only the built-in ones are known
+fn currency_registry(args: &[String]) -> Result<CurrencyRegistry> {
+    Ok(match flag_value(args, "--currencies")? {
+        Some(path) => load_toml_or_json::<CurrencyRegistry>(Path::new(path))?,
+        None => CurrencyRegistry::default(),
+    })
+}
+
+/// Reads `--labels <file>`, whose field labels are recognized on top of the built-in ones
+fn label_set(args: &[String]) -> Result<LabelSet> {
+    Ok(match flag_value(args, "--labels")? {
+        Some(path) => load_toml_or_json::<LabelSet>(Path::new(path))?,
+        None => LabelSet::default(),
+    })
+}
+
+/// Reads `--numbers <point|comma|locale>`; without it, or with `auto`, the number format is
+/// detected from each catalog. `--currencies` adds custom currencies and `--labels` custom labels
+fn parse_options(args: &[String]) -> Result<ParseOptions> {
+    let numbers = match flag_value(args, "--numbers")? {
+        None | Some("auto") => None,
+        Some(format) => Some(format.parse()?),
+    };
+    Ok(ParseOptions {
+        numbers,
+        currencies: currency_registry(args)?,
+        labels: label_set(args)?,
+    })
+}
+
+/// Reads `--allergens <file>`, which replaces the built-in list of flagged substances
+fn allergen_list(args: &[String]) -> Result<AllergenList> {
+    Ok(match flag_value(args, "--allergens")? {
+        Some(path) => load_toml_or_json::<AllergenList>(Path::new(path))?,
+        None => AllergenList::builtin(),
+    })
+}
+
+/// Reads `--lexicon <file>`, which replaces the built-in review sentiment lexicon
+fn lexicon(args: &[String]) -> Result<Lexicon> {
+    Ok(match flag_value(args, "--lexicon")? {
+        Some(path) => load_toml_or_json::<Lexicon>(Path::new(path))?,
+        None => Lexicon::builtin(),
+    })
+}
+
+/// Reads `--inci`, which adds INCI names to exports, and `--synonyms <file>`, which implies it
+/// and extends the bundled synonym dictionary
+fn inci_dictionary(args: &[String]) -> Result<Option<InciDictionary>> {
+    let synonyms = flag_value(args, "--synonyms")?;
+    if synonyms.is_none() && !has_flag(args, "--inci") {
+        return Ok(None);
+    }
+    let mut dictionary = InciDictionary::builtin();
+    if let Some(path) = synonyms {
+        dictionary.extend(load_toml_or_json::<InciDictionary>(Path::new(path))?);
+    }
+    Ok(Some(dictionary))
+}
+
+/// Options shared by the ways of exporting a catalog to JSON
+struct Export {
+    parse: ParseOptions,
+    spans: bool,
+    strict: bool,
+    allergens: AllergenList,
+    inci: Option<InciDictionary>,
+    lexicon: Lexicon,
+    conversion: Option<Conversion>,
+}
+
+impl Export {
+    fn from_args(args: &[String]) -> Result<Self> {
+        Ok(Export {
+            parse: parse_options(args)?,
+            spans: has_flag(args, "--spans"),
+            strict: has_flag(args, "--strict"),
+            allergens: allergen_list(args)?,
+            inci: inci_dictionary(args)?,
+            lexicon: lexicon(args)?,
+            conversion: Conversion::from_args(args)?,
+        })
+    }
+
+    /// With `--inci`, warns about every ingredient without an INCI name
+    fn warn_unmapped(&self, path: &str, products: &[Product]) {
+        let Some(dictionary) = &self.inci else {
+            return;
+        };
+        for product in products {
+            for ingredient in dictionary.normalize(&product.ingredients) {
+                if ingredient.inci.is_none() {
+                    eprintln!(
+                        "{}: warning: {}: no INCI name for `{}`",
+                        path, product.product_name, ingredient.name
+                    );
+                }
+            }
+        }
+    }
+
+    /// With `--strict`, warns about every extra field, whose label the parser does not know, and
+    /// with `--inci`, about every ingredient without an INCI name
+    fn warn(&self, path: &str, products: &[Product]) {
+        self.warn_unmapped(path, products);
+        if !self.strict {
+            return;
+        }
+        for product in products {
+            let mut labels: Vec<(Option<usize>, &String)> = product
+                .extra
+                .keys()
+                .map(|label| {
+                    let span = product.source_map.as_ref().and_then(|m| m.extra.get(label));
+                    (span.map(|s| s.line), label)
+                })
+                .collect();
+            labels.sort();
+            for (line, label) in labels {
+                match line {
+                    Some(line) => eprintln!(
+                        "{}: warning: line {}: unknown label `{}`",
+                        path, line, label
+                    ),
+                    None => eprintln!(
+                        "{}: warning: {}: unknown label `{}`",
+                        path, product.product_name, label
+                    ),
+                }
+            }
+        }
+    }
+
+    /// Prepares parsed products for output
+    fn apply(&self, products: &mut [Product]) {
+        if !self.spans {
+            products.iter_mut().for_each(|p| p.source_map = None);
+        }
+        self.allergens.annotate(products);
+        if let Some(dictionary) = &self.inci {
+            dictionary.annotate(products);
+        }
+        self.lexicon.annotate(products);
+        if let Some(conversion) = &self.conversion {
+            conversion.convert(products);
+        }
+    }
+}
+
 fn print_info() {
     println!("Cosmetics Parser:");
     println!("  cargo run <input_file> <output_file>          Parses a cosmetics data file and outputs to JSON format.");
+    println!("  cargo run <input> <output> --parallel         Parses product blocks in parallel on all CPU cores.");
+    println!("  cargo run <input> <output> --stream           Parses one product at a time with bounded memory; use - as input for stdin.");
+    println!("  cargo run <input> <output> --spans            Adds the line, column and byte range of every field to the JSON output.");
+    println!("  cargo run <input> <output> --strict           Warns about every line with an unknown label; such lines are kept under extra.");
+    println!("  cargo run <input> <output> --currency <code>  Converts every price into one currency; also works with batch, query and stats.");
+    println!("      [--rates <file>] [--date <YYYY-MM-DD>]    Exchange-rate table (default rates.toml) and the date whose rates apply (default today).");
+    println!("  cargo run -- ... --allergens <file>           Replaces the built-in list of allergens and restricted substances flagged in exports.");
+    println!("  cargo run <input> <output> --inci             Adds the INCI name of every ingredient and warns about ingredients it cannot map.");
+    println!("      [--synonyms <file>]                       Extends the bundled INCI synonym dictionary.");
+    println!("  cargo run -- ... --lexicon <file>             Replaces the built-in lexicon that review sentiment in exports, query and stats is scored with.");
+    println!("  cargo run -- ... --currencies <file>          Adds custom currency codes, symbols and decimal places to the built-in ones.");
+    println!("  cargo run -- ... --numbers <point|comma|en|uk|de>  Sets the decimal separator of prices and ratings instead of detecting it.");
+    println!("  cargo run -- ... --labels <file>              Adds field labels in other languages to the built-in Ukrainian ones.");
+    println!("  cargo run -- batch <output> <inputs...>       Parses files, directories or glob patterns into one merged JSON catalog.");
+    println!("  cargo run -- batch <dir> <inputs...> --split  Writes one JSON file per input into <dir> instead of merging.");
+    println!("  cargo run -- query <catalog> <expression>     Prints the products matching an expression such as 'price < 300 and availability'.");
+    println!("      [--sort <field> [asc|desc]] [--fields <field,...>]   Sorts the matches and keeps only the listed fields.");
+    println!("  cargo run -- allergens <catalog> [--json]     Reports which products contain which allergens or restricted substances.");
+    println!("  cargo run -- reviews <catalog> [--json]       Reports products whose review stars such as (4/5) disagree with their user ratings.");
+    println!("  cargo run -- conflicts <catalog> [--json]     Reports ingredients within a product that should not be combined.");
+    println!("      [--ids <id,...>] [--rules <file>]         Checks the listed products as one routine; the rule file replaces the built-in rules.");
+    println!("  cargo run -- recommend <catalog> <skin type>  Ranks the products for a skin type with a score breakdown for each.");
+    println!(
+        "      [--budget <price>] [--in-stock] [--exclude <ingredient,...>] [--top <n>] [--json]"
+    );
+    println!("  cargo run -- search <catalog> <words> [--top <n>] [--json]  Ranks the products matching any of the words.");
+    println!("  cargo run -- stats <catalog> [--json] [--top <n>]  Summarizes prices, stock, ratings, reviews, ingredients and skin types.");
+    println!("  cargo run -- diff <old> <new> [--json|--patch]  Reports added, removed and changed products between two catalogs.");
+    println!("  cargo run -- merge <output> <catalogs...>      Merges supplier catalogs into one, reporting every conflicting field.");
+    println!("      [--policy first-wins|last-wins|lowest-price|union|fail]   How conflicts are resolved (default first-wins).");
+    println!("  cargo run -- dedup <catalog> [--threshold <0-1>] [--json]  Clusters likely duplicates by name, ingredients and price.");
+    println!("      [--merge <output>] [--policy <policy>]    Writes the catalog with every cluster merged into one product.");
     println!("  cargo run -- --help                           Displays help information.");
     println!("  cargo run -- --credits                        Shows project credits.");
 }
 
-fn parse_file(input_path: &str, output_path: &str) -> Result<()> {
+fn parse_file(input_path: &str, output_path: &str, export: &Export) -> Result<()> {
     let mut input = String::new();
     File::open(input_path)?.read_to_string(&mut input)?;
 
-    let pairs = Grammar::parse(Rule::products, &input)
+    let input = export.parse.labels.normalize(&input);
+    let pairs = Grammar::parse(Rule::products, &input.text)
         .map_err(|e| anyhow!("Parsing failed: {}", e))?
         .next()
         .ok_or_else(|| anyhow!("No products found in input file"))?;
 
-    let products: Vec<Product> = pairs.into_inner().map(Product::from_pair).collect();
+    let numbers = export
+        .parse
+        .numbers
+        .or_else(|| {
+            pairs
+                .clone()
+                .into_inner()
+                .find_map(|p| NumberFormat::detect(&p))
+        })
+        .unwrap_or_default();
+    let mut products: Vec<Product> = pairs
+        .into_inner()
+        .map(|pair| {
+            check_product_with(&pair, &export.parse.currencies)
+                .map_err(|e| anyhow!("Parsing failed: {}", e))?;
+            let source_map = (export.spans || export.strict).then(|| {
+                let mut source_map = SourceMap::from_pair(&pair);
+                source_map.restore(&input);
+                source_map
+            });
+            let mut product = Product::from_pair_with(pair, numbers);
+            product.resolve_currencies(&export.parse.currencies);
+            product.source_map = source_map;
+            Ok(product)
+        })
+        .collect::<Result<_>>()?;
+    export.warn(input_path, &products);
+    export.apply(&mut products);
 
     let json_output = serde_json::to_string_pretty(&products)
         .map_err(|e| anyhow!("Failed to serialize data to JSON: {}", e))?;
@@ -59,4 +422,529 @@ fn parse_file(input_path: &str, output_path: &str) -> Result<()> {
     println!("Parsed data has been written to {}", output_path);
 
     Ok(())
-}
\ No newline at end of file
+}
+
+fn parse_stream(input_path: &str, output_path: &str, export: &Export) -> Result<()> {
+    let input: Box<dyn BufRead> = if input_path == "-" {
+        Box::new(io::stdin().lock())
+    } else {
+        Box::new(BufReader::new(File::open(input_path)?))
+    };
+    let mut output = BufWriter::new(File::create(output_path)?);
+
+    let mut count = 0;
+    let mut errors = 0;
+    output.write_all(b"[")?;
+    for result in ProductReader::with_options(input, export.parse.clone()) {
+        match result {
+            Ok(mut product) => {
+                export.warn(input_path, std::slice::from_ref(&product));
+                export.apply(std::slice::from_mut(&mut product));
+                output.write_all(if count == 0 { b"\n" } else { b",\n" })?;
+                serde_json::to_writer_pretty(&mut output, &product)
+                    .map_err(|e| anyhow!("Failed to serialize data to JSON: {}", e))?;
+                count += 1;
+            }
+            Err(ParseError::Io(e)) => return Err(e.into()),
+            Err(e) => {
+                eprintln!("{}: {}", input_path, e);
+                errors += 1;
+            }
+        }
+    }
+    output.write_all(b"\n]\n")?;
+    output.flush()?;
+
+    println!(
+        "Parsed {} products ({} errors); data has been written to {}",
+        count, errors, output_path
+    );
+
+    Ok(())
+}
+
+fn parse_file_parallel(input_path: &str, output_path: &str, export: &Export) -> Result<()> {
+    let input = fs::read_to_string(input_path)?;
+    let (mut products, errors) = parallel::parse_catalog_parallel_with(&input, &export.parse);
+    export.warn(input_path, &products);
+    export.apply(&mut products);
+
+    for error in &errors {
+        eprintln!("{}: {}", input_path, error);
+    }
+
+    batch::write_json(Path::new(output_path), &products)?;
+
+    println!(
+        "Parsed {} products ({} errors); data has been written to {}",
+        products.len(),
+        errors.len(),
+        output_path
+    );
+
+    Ok(())
+}
+
+fn parse_batch(inputs: &[String], output: &str, split: bool, export: &Export) -> Result<()> {
+    let paths = batch::collect_inputs(inputs)?;
+    let mut reports = batch::parse_paths_with(&paths, &export.parse);
+    for report in &mut reports {
+        export.warn(&report.path.display().to_string(), &report.products);
+        export.apply(&mut report.products);
+    }
+
+    print_summary(&reports);
+
+    if split {
+        let written = batch::write_separate(&reports, Path::new(output))?;
+        println!(
+            "Parsed data has been written to {} files in {}",
+            written.len(),
+            output
+        );
+    } else {
+        let merged = batch::merge_reports(reports);
+        batch::write_json(Path::new(output), &merged)?;
+        println!("Parsed data has been written to {}", output);
+    }
+
+    Ok(())
+}
+
+fn print_summary(reports: &[FileReport]) {
+    let width = reports
+        .iter()
+        .map(|r| r.path.display().to_string().len())
+        .max()
+        .unwrap_or(0)
+        .max(4);
+
+    println!("{:<width$}  {:>8}  {:>6}", "File", "Products", "Errors");
+    for report in reports {
+        println!(
+            "{:<width$}  {:>8}  {:>6}",
+            report.path.display().to_string(),
+            report.products.len(),
+            report.errors.len()
+        );
+        for error in &report.errors {
+            println!("    {}", error);
+        }
+    }
+    println!(
+        "{:<width$}  {:>8}  {:>6}",
+        "Total",
+        reports.iter().map(|r| r.products.len()).sum::<usize>(),
+        reports.iter().map(|r| r.errors.len()).sum::<usize>()
+    );
+}
+
+/// Loads a catalog from a JSON file written by this tool, or parses it from a Markdown catalog
+fn load_catalog(path: &str, options: &ParseOptions) -> Result<Vec<Product>> {
+    let input = fs::read_to_string(path)?;
+    if path.ends_with(".json") {
+        return serde_json::from_str(&input)
+            .map_err(|e| anyhow!("Failed to read JSON catalog {}: {}", path, e));
+    }
+
+    let (mut products, errors) = parse_catalog_with(&input, options);
+    for error in &errors {
+        eprintln!("{}: {}", path, error);
+    }
+    products.iter_mut().for_each(|p| p.source_map = None);
+    Ok(products)
+}
+
+fn run_query(args: &[String]) -> Result<()> {
+    let mut positional = Vec::new();
+    let mut sort = None;
+    let mut fields = None;
+
+    let mut iter = args.iter().peekable();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--sort" => {
+                let field: Field = iter
+                    .next()
+                    .ok_or_else(|| anyhow!("--sort needs a field name"))?
+                    .parse()?;
+                let order = match iter.peek().map(|s| s.as_str()) {
+                    Some("desc") => SortOrder::Desc,
+                    Some("asc") => SortOrder::Asc,
+                    _ => {
+                        sort = Some((field, SortOrder::Asc));
+                        continue;
+                    }
+                };
+                iter.next();
+                sort = Some((field, order));
+            }
+            "--fields" => {
+                let list = iter
+                    .next()
+                    .ok_or_else(|| anyhow!("--fields needs a comma-separated list"))?;
+                fields = Some(
+                    list.split(',')
+                        .map(|f| f.trim().parse())
+                        .collect::<Result<Vec<Field>, _>>()?,
+                );
+            }
+            flag if VALUE_FLAGS.contains(&flag) => {
+                iter.next();
+            }
+            _ => positional.push(arg),
+        }
+    }
+
+    let [catalog, expression] = positional[..] else {
+        eprintln!("Error: query needs a catalog file and an expression.");
+        print_info();
+        return Ok(());
+    };
+
+    let query = Query::parse(expression)?;
+    let mut products = load_catalog(catalog, &parse_options(args)?)?;
+    allergen_list(args)?.annotate(&mut products);
+    lexicon(args)?.annotate(&mut products);
+    if let Some(conversion) = Conversion::from_args(args)? {
+        conversion.convert(&mut products);
+    }
+    let mut matches = query.filter(&products);
+    if let Some((field, order)) = sort {
+        sort_products(&mut matches, field, order);
+    }
+
+    let json_output = match fields {
+        Some(fields) => {
+            let projected: Vec<_> = matches.iter().map(|p| project(p, &fields)).collect();
+            serde_json::to_string_pretty(&projected)
+        }
+        None => serde_json::to_string_pretty(&matches),
+    }
+    .map_err(|e| anyhow!("Failed to serialize data to JSON: {}", e))?;
+
+    println!("{}", json_output);
+    eprintln!("{} of {} products match", matches.len(), products.len());
+
+    Ok(())
+}
+
+fn run_allergens(args: &[String]) -> Result<()> {
+    let Some(catalog) = positional(args).into_iter().next() else {
+        eprintln!("Error: allergens needs a catalog file.");
+        print_info();
+        return Ok(());
+    };
+
+    let products = load_catalog(&catalog, &parse_options(args)?)?;
+    let report = allergen_list(args)?.report(&products);
+
+    if has_flag(args, "--json") {
+        let json_output = serde_json::to_string_pretty(&report)
+            .map_err(|e| anyhow!("Failed to serialize data to JSON: {}", e))?;
+        println!("{}", json_output);
+    } else {
+        print!("{}", report);
+    }
+
+    Ok(())
+}
+
+fn run_reviews(args: &[String]) -> Result<()> {
+    let Some(catalog) = positional(args).into_iter().next() else {
+        eprintln!("Error: reviews needs a catalog file.");
+        print_info();
+        return Ok(());
+    };
+
+    let products = load_catalog(&catalog, &parse_options(args)?)?;
+    let report = reviews::report(&products);
+
+    if has_flag(args, "--json") {
+        let json_output = serde_json::to_string_pretty(&report)
+            .map_err(|e| anyhow!("Failed to serialize data to JSON: {}", e))?;
+        println!("{}", json_output);
+    } else {
+        print!("{}", report);
+    }
+
+    Ok(())
+}
+
+fn run_conflicts(args: &[String]) -> Result<()> {
+    let Some(catalog) = positional(args).into_iter().next() else {
+        eprintln!("Error: conflicts needs a catalog file.");
+        print_info();
+        return Ok(());
+    };
+    let rules = match flag_value(args, "--rules")? {
+        Some(path) => load_toml_or_json::<ConflictRules>(Path::new(path))?,
+        None => ConflictRules::builtin(),
+    };
+
+    let products = load_catalog(&catalog, &parse_options(args)?)?;
+    let conflicts = match flag_value(args, "--ids")? {
+        Some(ids) => {
+            let ids = ids
+                .split(',')
+                .map(|id| {
+                    id.trim()
+                        .parse()
+                        .map_err(|e| anyhow!("Invalid product id {}: {}", id, e))
+                })
+                .collect::<Result<Vec<u64>>>()?;
+            rules.check_ids(&products, &ids)?
+        }
+        None => rules.check_catalog(&products),
+    };
+
+    if has_flag(args, "--json") {
+        let json_output = serde_json::to_string_pretty(&conflicts)
+            .map_err(|e| anyhow!("Failed to serialize data to JSON: {}", e))?;
+        println!("{}", json_output);
+    } else {
+        for conflict in &conflicts {
+            println!("{}", conflict);
+        }
+    }
+    eprintln!("{} conflict(s) found", conflicts.len());
+
+    Ok(())
+}
+
+fn run_recommend(args: &[String]) -> Result<()> {
+    let [catalog, skin_type] = &positional(args)[..] else {
+        eprintln!("Error: recommend needs a catalog file and a skin type.");
+        print_info();
+        return Ok(());
+    };
+
+    let mut preferences = Preferences::new(skin_type);
+    preferences.budget = match flag_value(args, "--budget")? {
+        Some(budget) => Some(
+            budget
+                .parse()
+                .map_err(|e| anyhow!("Invalid --budget {}: {}", budget, e))?,
+        ),
+        None => None,
+    };
+    preferences.in_stock = has_flag(args, "--in-stock");
+    if let Some(exclude) = flag_value(args, "--exclude")? {
+        preferences.exclude = exclude
+            .split(',')
+            .map(|ingredient| ingredient.trim().to_string())
+            .filter(|ingredient| !ingredient.is_empty())
+            .collect();
+    }
+    let top = match flag_value(args, "--top")? {
+        Some(top) => top.parse()?,
+        None => 10,
+    };
+
+    let mut products = load_catalog(catalog, &parse_options(args)?)?;
+    if let Some(conversion) = Conversion::from_args(args)? {
+        conversion.convert(&mut products);
+    }
+    let mut recommendations = recommend(&products, &preferences);
+    recommendations.truncate(top);
+
+    if has_flag(args, "--json") {
+        let json_output = serde_json::to_string_pretty(&recommendations)
+            .map_err(|e| anyhow!("Failed to serialize data to JSON: {}", e))?;
+        println!("{}", json_output);
+    } else {
+        for recommendation in &recommendations {
+            println!("{}", recommendation);
+        }
+    }
+
+    Ok(())
+}
+
+fn run_search(args: &[String]) -> Result<()> {
+    let [catalog, query] = &positional(args)[..] else {
+        eprintln!("Error: search needs a catalog file and the words to search for.");
+        print_info();
+        return Ok(());
+    };
+    let top = match flag_value(args, "--top")? {
+        Some(top) => top.parse()?,
+        None => 10,
+    };
+
+    let catalog = Catalog::new(load_catalog(catalog, &parse_options(args)?)?);
+    let mut hits = catalog.search(query);
+    let found = hits.len();
+    hits.truncate(top);
+
+    if has_flag(args, "--json") {
+        let json_output = serde_json::to_string_pretty(&hits)
+            .map_err(|e| anyhow!("Failed to serialize data to JSON: {}", e))?;
+        println!("{}", json_output);
+    } else {
+        for hit in &hits {
+            println!("{:.3}  {}", hit.score, hit.product.product_name);
+        }
+    }
+    eprintln!("{} of {} products match", found, catalog.products().len());
+
+    Ok(())
+}
+
+fn run_stats(args: &[String]) -> Result<()> {
+    let Some(catalog) = positional(args).into_iter().next() else {
+        eprintln!("Error: stats needs a catalog file.");
+        print_info();
+        return Ok(());
+    };
+    let top = match flag_value(args, "--top")? {
+        Some(top) => top.parse()?,
+        None => 10,
+    };
+
+    let mut products = load_catalog(&catalog, &parse_options(args)?)?;
+    if let Some(conversion) = Conversion::from_args(args)? {
+        conversion.convert(&mut products);
+    }
+    let stats = CatalogStats::with_lexicon(&products, top, &lexicon(args)?);
+
+    if has_flag(args, "--json") {
+        let json_output = serde_json::to_string_pretty(&stats)
+            .map_err(|e| anyhow!("Failed to serialize data to JSON: {}", e))?;
+        println!("{}", json_output);
+    } else {
+        print!("{}", stats);
+    }
+
+    Ok(())
+}
+
+fn run_diff(args: &[String]) -> Result<()> {
+    let [old_path, new_path] = &positional(args)[..] else {
+        eprintln!("Error: diff needs an old and a new catalog file.");
+        print_info();
+        return Ok(());
+    };
+
+    let options = parse_options(args)?;
+    let old = load_catalog(old_path, &options)?;
+    let new = load_catalog(new_path, &options)?;
+    let diff = CatalogDiff::new(&old, &new);
+
+    if has_flag(args, "--patch") || has_flag(args, "--json") {
+        let value = if has_flag(args, "--patch") {
+            diff.to_json_patch(&old, &new)
+        } else {
+            serde_json::to_value(&diff)?
+        };
+        let json_output = serde_json::to_string_pretty(&value)
+            .map_err(|e| anyhow!("Failed to serialize data to JSON: {}", e))?;
+        println!("{}", json_output);
+    } else {
+        print!("{}", diff);
+    }
+
+    Ok(())
+}
+
+fn run_merge(args: &[String]) -> Result<()> {
+    let mut paths = Vec::new();
+    let mut policy = ConflictPolicy::FirstWins;
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--policy" => {
+                policy = iter
+                    .next()
+                    .ok_or_else(|| anyhow!("--policy needs a policy name"))?
+                    .parse()?;
+            }
+            flag if VALUE_FLAGS.contains(&flag) => {
+                iter.next();
+            }
+            _ => paths.push(arg),
+        }
+    }
+
+    let Some((output, inputs)) = paths.split_first().filter(|(_, inputs)| !inputs.is_empty())
+    else {
+        eprintln!("Error: merge needs an output path and at least one catalog.");
+        print_info();
+        return Ok(());
+    };
+
+    let options = parse_options(args)?;
+    let mut catalogs = inputs
+        .iter()
+        .map(|path| load_catalog(path, &options))
+        .collect::<Result<Vec<_>>>()?;
+    if let Some(conversion) = Conversion::from_args(args)? {
+        for catalog in &mut catalogs {
+            conversion.convert(catalog);
+        }
+    }
+    let report = merge_catalogs(&catalogs, policy)?;
+
+    for (i, path) in inputs.iter().enumerate() {
+        println!("catalog {}: {}", i + 1, path);
+    }
+    print!("{}", report);
+
+    let json_output = serde_json::to_string_pretty(&report.products)
+        .map_err(|e| anyhow!("Failed to serialize data to JSON: {}", e))?;
+    let mut file = File::create(output)?;
+    file.write_all(json_output.as_bytes())?;
+    println!("Merged data has been written to {}", output);
+
+    Ok(())
+}
+
+fn run_dedup(args: &[String]) -> Result<()> {
+    let [catalog] = &positional(args)[..] else {
+        eprintln!("Error: dedup needs a catalog file.");
+        print_info();
+        return Ok(());
+    };
+
+    let mut options = DedupOptions::default();
+    if let Some(threshold) = flag_value(args, "--threshold")? {
+        options.threshold = threshold
+            .parse()
+            .map_err(|e| anyhow!("Invalid --threshold {}: {}", threshold, e))?;
+    }
+    let policy = match flag_value(args, "--policy")? {
+        Some(policy) => policy.parse()?,
+        None => ConflictPolicy::FirstWins,
+    };
+
+    let mut products = load_catalog(catalog, &parse_options(args)?)?;
+    if let Some(conversion) = Conversion::from_args(args)? {
+        conversion.convert(&mut products);
+    }
+    let report = find_duplicates(&products, &options);
+
+    if has_flag(args, "--json") {
+        let json_output = serde_json::to_string_pretty(&report)
+            .map_err(|e| anyhow!("Failed to serialize data to JSON: {}", e))?;
+        println!("{}", json_output);
+    } else {
+        print!("{}", report);
+    }
+
+    if let Some(output) = flag_value(args, "--merge")? {
+        let merged = report.merge(&products, policy)?;
+        let json_output = serde_json::to_string_pretty(&merged.products)
+            .map_err(|e| anyhow!("Failed to serialize data to JSON: {}", e))?;
+        let mut file = File::create(output)?;
+        file.write_all(json_output.as_bytes())?;
+        eprintln!(
+            "Merged {} cluster(s) into {} products in {}",
+            report.clusters.len(),
+            merged.products.len(),
+            output
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,313 @@
+use crate::reviews::ReviewStars;
+use crate::Product;
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+
+/// An error produced while merging catalogs
+#[derive(Error, Debug)]
+pub enum MergeError {
+    #[error("unknown conflict policy `{0}`, expected first-wins, last-wins, lowest-price, union or fail")]
+    UnknownPolicy(String),
+    #[error("merge failed on conflict: {0}")]
+    Conflict(Conflict),
+    #[error("cannot pick the lowest price of {product_name} from prices in {}; convert the catalogs to one currency first", .currencies.join(", "))]
+    MixedCurrencies {
+        product_name: String,
+        currencies: Vec<String>,
+    },
+}
+
+/// How to resolve a product whose fields differ between catalogs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictPolicy {
+    /// Keep the product from the first catalog it appears in
+    FirstWins,
+    /// Keep the product from the last catalog it appears in
+    LastWins,
+    /// Keep the cheapest offer, the earliest one on ties. Offers in different currencies are an
+    /// error, since their prices cannot be compared
+    LowestPrice,
+    /// Keep the first product but combine the reviews and user ratings of all of them
+    Union,
+    /// Stop at the first conflict
+    Fail,
+}
+
+impl ConflictPolicy {
+    pub const ALL: [ConflictPolicy; 5] = [
+        ConflictPolicy::FirstWins,
+        ConflictPolicy::LastWins,
+        ConflictPolicy::LowestPrice,
+        ConflictPolicy::Union,
+        ConflictPolicy::Fail,
+    ];
+
+    /// The name used on the command line, e.g. `lowest-price`
+    pub fn name(&self) -> &'static str {
+        match self {
+            ConflictPolicy::FirstWins => "first-wins",
+            ConflictPolicy::LastWins => "last-wins",
+            ConflictPolicy::LowestPrice => "lowest-price",
+            ConflictPolicy::Union => "union",
+            ConflictPolicy::Fail => "fail",
+        }
+    }
+}
+
+impl FromStr for ConflictPolicy {
+    type Err = MergeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ConflictPolicy::ALL
+            .into_iter()
+            .find(|policy| policy.name() == s)
+            .ok_or_else(|| MergeError::UnknownPolicy(s.to_string()))
+    }
+}
+
+impl fmt::Display for ConflictPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// The value one catalog has for a conflicting field; `catalog` is the 0-based position of the
+/// catalog in the merge input
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ConflictValue {
+    pub catalog: usize,
+    pub value: Value,
+}
+
+/// How a conflict was resolved
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Resolution {
+    /// The value of the catalog at this position was kept
+    Kept { catalog: usize },
+    /// The values of all catalogs were combined
+    Merged,
+}
+
+impl fmt::Display for Resolution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Resolution::Kept { catalog } => write!(f, "kept catalog {}", catalog + 1),
+            Resolution::Merged => write!(f, "merged"),
+        }
+    }
+}
+
+/// A field of one product that differs between the catalogs it appears in
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Conflict {
+    pub product_name: String,
+    pub field: String,
+    /// The value of every occurrence of the product, in catalog order
+    pub values: Vec<ConflictValue>,
+    pub resolution: Resolution,
+}
+
+impl fmt::Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {} differs (", self.product_name, self.field)?;
+        for (i, value) in self.values.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "catalog {}: {}", value.catalog + 1, value.value)?;
+        }
+        write!(f, ")")
+    }
+}
+
+/// The merged catalog together with every conflict met on the way
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct MergeReport {
+    pub products: Vec<Product>,
+    pub conflicts: Vec<Conflict>,
+}
+
+impl fmt::Display for MergeReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Merged {} products with {} conflict(s)",
+            self.products.len(),
+            self.conflicts.len()
+        )?;
+        for conflict in &self.conflicts {
+            writeln!(f, "  {} -> {}", conflict, conflict.resolution)?;
+        }
+        Ok(())
+    }
+}
+
+/// The fields compared between occurrences of a product. Ids are left out because every supplier
+/// numbers its own catalog, allergens and INCI names because they follow from the ingredients,
+/// and review stars and sentiment because they follow from the reviews
+fn compared_fields(product: &Product) -> Map<String, Value> {
+    let Value::Object(mut fields) = json!(product) else {
+        return Map::new();
+    };
+    fields.remove("product_id");
+    fields.remove("source_map");
+    fields.remove("allergens");
+    fields.remove("inci");
+    fields.remove("sentiment");
+    fields.remove("review_stars");
+    fields
+}
+
+/// Adds the reviews and user ratings of `other` that `product` does not have yet, together with
+/// the stars of the added reviews. Ratings are combined as a multiset, so a rating given twice
+/// in one catalog is kept twice
+fn union(product: &mut Product, other: &Product) {
+    for (i, review) in other.reviews.iter().enumerate() {
+        if !product.reviews.contains(review) {
+            let stars = other.review_stars.iter().find(|stars| stars.review == i);
+            if let Some(stars) = stars {
+                product.review_stars.push(ReviewStars {
+                    review: product.reviews.len(),
+                    ..*stars
+                });
+            }
+            product.reviews.push(review.clone());
+        }
+    }
+    for rating in &other.user_ratings {
+        let have = product.user_ratings.iter().filter(|r| *r == rating).count();
+        let want = other.user_ratings.iter().filter(|r| *r == rating).count();
+        if have < want {
+            product.user_ratings.push(*rating);
+        }
+    }
+}
+
+/// Resolves all occurrences of one product, given as (catalog, product) pairs in catalog order
+pub(crate) fn merge_product(
+    occurrences: &[(usize, &Product)],
+    policy: ConflictPolicy,
+) -> Result<(Product, Vec<Conflict>), MergeError> {
+    if policy == ConflictPolicy::LowestPrice {
+        let mut currencies: Vec<String> = Vec::new();
+        for (_, product) in occurrences {
+            let currency = product
+                .currency
+                .as_ref()
+                .map_or_else(|| String::from("no currency"), |c| c.to_string());
+            if !currencies.contains(&currency) {
+                currencies.push(currency);
+            }
+        }
+        if currencies.len() > 1 {
+            return Err(MergeError::MixedCurrencies {
+                product_name: occurrences[0].1.product_name.clone(),
+                currencies,
+            });
+        }
+    }
+
+    let winner = match policy {
+        ConflictPolicy::LastWins => occurrences.len() - 1,
+        ConflictPolicy::LowestPrice => (0..occurrences.len())
+            .min_by(|&a, &b| occurrences[a].1.price.total_cmp(&occurrences[b].1.price))
+            .unwrap_or(0),
+        _ => 0,
+    };
+    let (winner_catalog, winner_product) = occurrences[winner];
+    let mut product = winner_product.clone();
+
+    let fields: Vec<(usize, Map<String, Value>)> = occurrences
+        .iter()
+        .map(|(catalog, p)| (*catalog, compared_fields(p)))
+        .collect();
+
+    // Fields left out of the JSON when empty, such as `original_price`, may only appear in some
+    // occurrences
+    let mut names: Vec<&String> = Vec::new();
+    for name in fields.iter().flat_map(|(_, f)| f.keys()) {
+        if !names.contains(&name) {
+            names.push(name);
+        }
+    }
+
+    let mut conflicts = Vec::new();
+    for field in names {
+        let values: Vec<ConflictValue> = fields
+            .iter()
+            .map(|(catalog, f)| ConflictValue {
+                catalog: *catalog,
+                value: f.get(field).cloned().unwrap_or(Value::Null),
+            })
+            .collect();
+        if values.iter().all(|v| v.value == values[0].value) {
+            continue;
+        }
+
+        let mut conflict = Conflict {
+            product_name: product.product_name.clone(),
+            field: field.clone(),
+            values,
+            resolution: Resolution::Kept {
+                catalog: winner_catalog,
+            },
+        };
+        match policy {
+            ConflictPolicy::Fail => return Err(MergeError::Conflict(conflict)),
+            ConflictPolicy::Union if field == "reviews" || field == "user_ratings" => {
+                conflict.resolution = Resolution::Merged;
+            }
+            _ => {}
+        }
+        conflicts.push(conflict);
+    }
+
+    if policy == ConflictPolicy::Union {
+        for (_, other) in &occurrences[1..] {
+            union(&mut product, other);
+        }
+    }
+
+    Ok((product, conflicts))
+}
+
+/// Merges several catalogs into one. Products are matched by a case-insensitive comparison of
+/// their names, since `*Product N*` ids are only unique within a single catalog. The merged
+/// catalog lists products in the order they first appear
+pub fn merge_catalogs(
+    catalogs: &[Vec<Product>],
+    policy: ConflictPolicy,
+) -> Result<MergeReport, MergeError> {
+    let mut groups: Vec<Vec<(usize, &Product)>> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+    for (catalog, products) in catalogs.iter().enumerate() {
+        for product in products {
+            let key = product.product_name.trim().to_lowercase();
+            match index.get(&key) {
+                Some(&i) => groups[i].push((catalog, product)),
+                None => {
+                    index.insert(key, groups.len());
+                    groups.push(vec![(catalog, product)]);
+                }
+            }
+        }
+    }
+
+    let mut products = Vec::with_capacity(groups.len());
+    let mut conflicts = Vec::new();
+    for occurrences in &groups {
+        let (product, product_conflicts) = merge_product(occurrences, policy)?;
+        products.push(product);
+        conflicts.extend(product_conflicts);
+    }
+
+    Ok(MergeReport {
+        products,
+        conflicts,
+    })
+}
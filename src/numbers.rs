@@ -0,0 +1,118 @@
+use crate::Rule;
+use pest::iterators::Pair;
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+
+/// An unrecognized name given for a number format
+#[derive(Error, Debug)]
+#[error("unknown number format `{0}`, expected point, comma or a locale such as en, uk or de")]
+pub struct UnknownNumberFormat(pub String);
+
+/// Which character separates the decimals in a catalog's amounts. The other one, as well as
+/// spaces, non-breaking spaces and apostrophes, separates groups of thousands
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NumberFormat {
+    /// `1,299.99` or `1 299.99`, as in English catalogs
+    #[default]
+    DecimalPoint,
+    /// `1.299,99` or `1 299,99`, as in Ukrainian and German catalogs
+    DecimalComma,
+}
+
+/// Characters that only ever separate groups of thousands
+fn is_group_space(c: char) -> bool {
+    matches!(c, ' ' | '\u{a0}' | '\u{202f}' | '\'')
+}
+
+impl NumberFormat {
+    pub fn decimal_separator(&self) -> char {
+        match self {
+            NumberFormat::DecimalPoint => '.',
+            NumberFormat::DecimalComma => ',',
+        }
+    }
+
+    fn with_decimal(separator: char) -> Self {
+        if separator == ',' {
+            NumberFormat::DecimalComma
+        } else {
+            NumberFormat::DecimalPoint
+        }
+    }
+
+    /// The format a single amount reveals, if any. `299,99` and `1.299,99` can only be written
+    /// with a decimal comma, while `1,299` could be either and reveals nothing
+    pub fn evidence(amount: &str) -> Option<Self> {
+        let last = amount.rfind(['.', ','])?;
+        let separator = if amount[last..].starts_with(',') {
+            ','
+        } else {
+            '.'
+        };
+        let other = if separator == ',' { '.' } else { ',' };
+
+        if amount.contains(other) {
+            return Some(NumberFormat::with_decimal(separator));
+        }
+        if amount.matches(separator).count() > 1 {
+            return Some(NumberFormat::with_decimal(other));
+        }
+        let decimals = amount[last + 1..]
+            .chars()
+            .take_while(char::is_ascii_digit)
+            .count();
+        if decimals != 3 || amount[..last].chars().any(is_group_space) {
+            return Some(NumberFormat::with_decimal(separator));
+        }
+        None
+    }
+
+    /// The format shown by the first amount of a parsed product that reveals one
+    pub fn detect(pair: &Pair<Rule>) -> Option<Self> {
+        pair.clone()
+            .into_inner()
+            .flatten()
+            .filter(|p| p.as_rule() == Rule::amount)
+            .find_map(|p| NumberFormat::evidence(p.as_str()))
+    }
+
+    /// Reads an amount such as `1 299,99`. An amount that reveals its own format is read that
+    /// way; this format only decides ambiguous ones like `1.299`
+    pub fn parse(&self, amount: &str) -> Option<f64> {
+        let decimal = NumberFormat::evidence(amount)
+            .unwrap_or(*self)
+            .decimal_separator();
+        let normalized: String = amount
+            .chars()
+            .filter(|c| !is_group_space(*c))
+            .filter_map(|c| match c {
+                c if c == decimal => Some('.'),
+                '.' | ',' => None,
+                c => Some(c),
+            })
+            .collect();
+        normalized.parse().ok()
+    }
+}
+
+impl FromStr for NumberFormat {
+    type Err = UnknownNumberFormat;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "point" | "en" | "us" => Ok(NumberFormat::DecimalPoint),
+            "comma" | "uk" | "ua" | "de" | "pl" | "fr" => Ok(NumberFormat::DecimalComma),
+            _ => Err(UnknownNumberFormat(s.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for NumberFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NumberFormat::DecimalPoint => write!(f, "point"),
+            NumberFormat::DecimalComma => write!(f, "comma"),
+        }
+    }
+}
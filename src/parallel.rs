@@ -0,0 +1,45 @@
+use crate::{parse_product_with, product_blocks_with, ParseError, ParseOptions, Product};
+use rayon::prelude::*;
+use rayon::ThreadPoolBuilder;
+
+/// Parses a catalog by splitting it on `*Product N*:` boundaries and parsing the blocks on the
+/// global rayon thread pool. Products and errors come back in the same order as in the input
+pub fn parse_catalog_parallel(input: &str) -> (Vec<Product>, Vec<ParseError>) {
+    parse_catalog_parallel_with(input, &ParseOptions::default())
+}
+
+/// Same as [`parse_catalog_parallel`], reading the catalog as `options` say. An undetermined
+/// number format is detected before the blocks are handed out, so the result matches
+/// [`crate::parse_catalog_with`]
+pub fn parse_catalog_parallel_with(
+    input: &str,
+    options: &ParseOptions,
+) -> (Vec<Product>, Vec<ParseError>) {
+    let blocks = product_blocks_with(input, &options.labels);
+    let options = options.detect(&blocks);
+    let results: Vec<Result<Product, ParseError>> = blocks
+        .into_par_iter()
+        .map(|block| parse_product_with(&block, &options))
+        .collect();
+
+    let mut products = Vec::with_capacity(results.len());
+    let mut errors = Vec::new();
+    for result in results {
+        match result {
+            Ok(product) => products.push(product),
+            Err(e) => errors.push(e),
+        }
+    }
+    (products, errors)
+}
+
+/// Same as [`parse_catalog_parallel_with`], but runs on a dedicated pool with `threads` worker
+/// threads
+pub fn parse_catalog_with_threads(
+    input: &str,
+    options: &ParseOptions,
+    threads: usize,
+) -> Result<(Vec<Product>, Vec<ParseError>), rayon::ThreadPoolBuildError> {
+    let pool = ThreadPoolBuilder::new().num_threads(threads).build()?;
+    Ok(pool.install(|| parse_catalog_parallel_with(input, options)))
+}
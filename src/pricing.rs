@@ -0,0 +1,164 @@
+use crate::currency::{Currency, CurrencyRegistry};
+use crate::exchange::round_to;
+use crate::numbers::NumberFormat;
+use crate::Rule;
+use chrono::NaiveDate;
+use pest::iterators::Pair;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// How far a stated discount may be from the computed one, in percentage points, so that
+/// percentages rounded either way are accepted
+const PERCENTAGE_TOLERANCE: f64 = 1.0;
+
+/// A price or discount the grammar accepts but that does not hold together
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum PricingError {
+    #[error("invalid price history date `{0}`")]
+    Date(String),
+    #[error("original price is in {original}, but the price is in {price}")]
+    CurrencyMismatch { price: String, original: String },
+    #[error("original price {original} is not above the price {price}")]
+    NotDiscounted { price: f64, original: f64 },
+    #[error("stated discount {stated}% does not match the computed {computed}%")]
+    Percentage { stated: f64, computed: f64 },
+}
+
+/// A price a product had from `date` on
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PricePoint {
+    pub date: NaiveDate,
+    pub price: f64,
+    #[serde(default)]
+    pub currency: Option<Currency>,
+}
+
+/// The price of a product together with its discount and history
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Pricing {
+    pub current: f64,
+    pub currency: Option<Currency>,
+    /// The price before the discount, when the product is on sale
+    pub original: Option<f64>,
+    /// The discount off the original price, in percent rounded to two decimal places
+    pub discount_percent: Option<f64>,
+    pub history: Vec<PricePoint>,
+}
+
+/// The discount from `original` to `current`, in percent rounded to two decimal places
+pub fn discount_percent(current: f64, original: f64) -> f64 {
+    round_to((original - current) / original * 100.0, 2)
+}
+
+/// Reads the amount and currency children of a `price`, `original_price` or `price_point` pair
+pub(crate) fn money<'a>(
+    pair: &Pair<'a, Rule>,
+    numbers: NumberFormat,
+) -> (Option<f64>, Option<&'a str>) {
+    let child = |rule: Rule| {
+        pair.clone()
+            .into_inner()
+            .find(|p| p.as_rule() == rule)
+            .map(|p| p.as_str())
+    };
+    (
+        child(Rule::amount).and_then(|a| numbers.parse(a)),
+        child(Rule::currency),
+    )
+}
+
+/// The `original_price` inside the `discount` of a `price` pair
+pub(crate) fn original_price<'a>(price: &Pair<'a, Rule>) -> Option<Pair<'a, Rule>> {
+    price
+        .clone()
+        .into_inner()
+        .filter(|p| p.as_rule() == Rule::discount)
+        .flat_map(|p| p.into_inner())
+        .find(|p| p.as_rule() == Rule::original_price)
+}
+
+impl PricePoint {
+    /// Reads a `price_point` pair, reading ambiguous amounts in the given format
+    pub fn from_pair(pair: Pair<Rule>, numbers: NumberFormat) -> Result<Self, PricingError> {
+        let date = pair
+            .clone()
+            .into_inner()
+            .find(|p| p.as_rule() == Rule::date)
+            .map_or("", |p| p.as_str());
+        let (price, currency) = money(&pair, numbers);
+        Ok(PricePoint {
+            date: NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                .map_err(|_| PricingError::Date(date.to_string()))?,
+            price: price.unwrap_or(0.0),
+            currency: currency.map(Currency::from_token),
+        })
+    }
+
+    /// Reads every `price_point` inside a `price_history` pair
+    pub fn history(pair: Pair<Rule>, numbers: NumberFormat) -> Result<Vec<Self>, PricingError> {
+        pair.into_inner()
+            .filter(|p| p.as_rule() == Rule::price_points)
+            .flat_map(|p| p.into_inner())
+            .filter(|p| p.as_rule() == Rule::price_point)
+            .map(|p| PricePoint::from_pair(p, numbers))
+            .collect()
+    }
+}
+
+/// Checks the discount of a `price` pair: the original price has to be in the same currency
+/// and above the price, and a stated percentage has to match the one the two prices give.
+/// Currency symbols are resolved through `currencies`
+pub fn check_discount(
+    price: &Pair<Rule>,
+    numbers: NumberFormat,
+    currencies: &CurrencyRegistry,
+) -> Result<(), PricingError> {
+    let Some(original) = original_price(price) else {
+        return Ok(());
+    };
+    let (current, currency) = money(price, numbers);
+    let (original, original_currency) = money(&original, numbers);
+    let (Some(current), Some(original)) = (current, original) else {
+        return Ok(());
+    };
+
+    if let (Some(price), Some(original)) = (currency, original_currency) {
+        let (price, original) = (
+            currencies.from_token(price),
+            currencies.from_token(original),
+        );
+        if price != original {
+            return Err(PricingError::CurrencyMismatch {
+                price: price.to_string(),
+                original: original.to_string(),
+            });
+        }
+    }
+    if original <= current {
+        return Err(PricingError::NotDiscounted {
+            price: current,
+            original,
+        });
+    }
+
+    let stated = price
+        .clone()
+        .into_inner()
+        .filter(|p| p.as_rule() == Rule::discount)
+        .flat_map(|p| p.into_inner())
+        .find(|p| p.as_rule() == Rule::percentage);
+    if let Some(stated) = stated {
+        let stated: f64 = stated
+            .as_str()
+            .trim_start_matches('-')
+            .trim_end_matches('%')
+            .replace(',', ".")
+            .parse()
+            .unwrap_or(0.0);
+        let computed = discount_percent(current, original);
+        if (stated - computed).abs() >= PERCENTAGE_TOLERANCE {
+            return Err(PricingError::Percentage { stated, computed });
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,242 @@
+use crate::availability::Availability;
+use crate::currency::Currency;
+use crate::numbers::NumberFormat;
+use crate::pricing::{self, PricePoint};
+use crate::quantity::Quantity;
+use crate::reviews::ReviewStars;
+use crate::{
+    check_number_lists, parse_block, ParseError, ParseOptions, Product, ProductBlock, Rule,
+};
+use pest::iterators::Pair;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// A borrowed view of a product whose text fields point into the parsed input.
+/// Useful for read-only scans like counting or filtering, where allocating a `String`
+/// for every field is wasted work. Convert it with `Product::from` when an owned copy is needed
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ProductRef<'a> {
+    pub product_id: Option<u64>,
+    pub product_name: &'a str,
+    pub skin_type: &'a str,
+    pub ingredients: &'a str,
+    pub rating: f64,
+    pub price: f64,
+    pub currency: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub original_price: Option<f64>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub price_history: Vec<PricePoint>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<Quantity>,
+    pub user_ratings: Vec<f64>,
+    pub recommendations: &'a str,
+    pub reviews: Vec<&'a str>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub review_stars: Vec<ReviewStars>,
+    pub availability: Availability,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub extra: BTreeMap<&'a str, &'a str>,
+}
+
+/// Returns the trimmed text of the first child of `pair` matching `rule`
+fn child_str<'a>(pair: &Pair<'a, Rule>, rule: Rule) -> Option<&'a str> {
+    pair.clone()
+        .into_inner()
+        .find(|p| p.as_rule() == rule)
+        .map(|p| p.as_str().trim())
+}
+
+impl<'a> ProductRef<'a> {
+    /// Builds a view of a `product` pair, reading its amounts in the format they reveal
+    pub fn from_pair(pair: Pair<'a, Rule>) -> Self {
+        let numbers = NumberFormat::detect(&pair).unwrap_or_default();
+        ProductRef::from_pair_with(pair, numbers)
+    }
+
+    /// Builds a view of a `product` pair, reading ambiguous amounts such as `1.299` in the
+    /// given format
+    pub fn from_pair_with(pair: Pair<'a, Rule>, numbers: NumberFormat) -> Self {
+        let amount = |pair: &Pair<'a, Rule>| {
+            child_str(pair, Rule::amount)
+                .and_then(|s| numbers.parse(s))
+                .unwrap_or(0.0)
+        };
+        let mut product = ProductRef {
+            product_id: None,
+            product_name: "",
+            skin_type: "",
+            ingredients: "",
+            rating: 0.0,
+            price: 0.0,
+            currency: None,
+            original_price: None,
+            price_history: Vec::new(),
+            size: None,
+            user_ratings: Vec::new(),
+            recommendations: "",
+            reviews: Vec::new(),
+            review_stars: Vec::new(),
+            availability: Availability::default(),
+            extra: BTreeMap::new(),
+        };
+
+        for inner_pair in pair.into_inner() {
+            match inner_pair.as_rule() {
+                Rule::product_name => {
+                    product.product_id =
+                        child_str(&inner_pair, Rule::product_id).and_then(|s| s.parse().ok());
+                    product.product_name = child_str(&inner_pair, Rule::any_text).unwrap_or("");
+                }
+                Rule::skin_type => {
+                    product.skin_type = child_str(&inner_pair, Rule::any_text).unwrap_or("")
+                }
+                Rule::ingredients => {
+                    product.ingredients = child_str(&inner_pair, Rule::any_text).unwrap_or("")
+                }
+                Rule::rating => product.rating = amount(&inner_pair),
+                Rule::price => {
+                    product.price = amount(&inner_pair);
+                    product.currency = child_str(&inner_pair, Rule::currency);
+                    product.original_price = pricing::original_price(&inner_pair)
+                        .and_then(|original| pricing::money(&original, numbers).0);
+                }
+                Rule::price_history => {
+                    product.price_history =
+                        PricePoint::history(inner_pair, numbers).unwrap_or_default()
+                }
+                Rule::size => {
+                    product.size = inner_pair
+                        .into_inner()
+                        .find(|p| p.as_rule() == Rule::measure)
+                        .and_then(|p| Quantity::from_pair(p, numbers))
+                }
+                Rule::user_ratings => {
+                    product.user_ratings = inner_pair
+                        .into_inner()
+                        .filter(|p| p.as_rule() == Rule::number_list)
+                        .flat_map(|p| p.into_inner())
+                        .filter_map(|p| match p.as_rule() {
+                            Rule::number => p.as_str().parse::<f64>().ok(),
+                            Rule::amount => numbers.parse(p.as_str()),
+                            _ => None,
+                        })
+                        .collect()
+                }
+                Rule::recommendations => {
+                    product.recommendations = child_str(&inner_pair, Rule::any_text).unwrap_or("")
+                }
+                Rule::reviews => {
+                    for (i, review) in inner_pair
+                        .into_inner()
+                        .filter(|p| p.as_rule() == Rule::review)
+                        .enumerate()
+                    {
+                        product.reviews.push(review.as_str().trim());
+                        product.review_stars.extend(
+                            review
+                                .into_inner()
+                                .find(|p| p.as_rule() == Rule::review_stars)
+                                .and_then(|p| ReviewStars::from_pair(p, i).ok()),
+                        );
+                    }
+                }
+                Rule::availability => {
+                    product.availability = inner_pair
+                        .into_inner()
+                        .find(|p| p.as_rule() == Rule::stock_status)
+                        .and_then(|p| Availability::from_pair(p).ok())
+                        .unwrap_or_default()
+                }
+                Rule::extra_field => {
+                    if let Some(label) = child_str(&inner_pair, Rule::extra_label) {
+                        let value = child_str(&inner_pair, Rule::any_text).unwrap_or("");
+                        product.extra.insert(label, value);
+                    }
+                }
+                _ => {}
+            }
+        }
+        product
+    }
+
+    /// Parses one product block without copying any of its text
+    pub fn parse(block: &ProductBlock<'a>) -> Result<Self, ParseError> {
+        ProductRef::parse_with(block, &ParseOptions::default())
+    }
+
+    /// Same as [`ProductRef::parse`], reading the block as `options` say: with their number
+    /// format when it is set, and their custom currencies and labels
+    pub fn parse_with(
+        block: &ProductBlock<'a>,
+        options: &ParseOptions,
+    ) -> Result<Self, ParseError> {
+        let text = options.labels.normalize(block.text);
+        let pair = parse_block(&block.with_text(&text.text), &options.currencies)?;
+        let numbers = options
+            .numbers
+            .or_else(|| NumberFormat::detect(&pair))
+            .unwrap_or_default();
+        check_number_lists(&pair, numbers, block.line)?;
+        Ok(ProductRef::from_pair_with(pair, numbers)
+            .rebase(|slice| text.original_str(block.text, slice)))
+    }
+
+    /// Moves every text field over to another copy of the text, with `text` mapping a slice of
+    /// the current text to the same slice of the other
+    fn rebase<'b>(self, text: impl Fn(&'a str) -> &'b str) -> ProductRef<'b> {
+        ProductRef {
+            product_id: self.product_id,
+            product_name: text(self.product_name),
+            skin_type: text(self.skin_type),
+            ingredients: text(self.ingredients),
+            rating: self.rating,
+            price: self.price,
+            currency: self.currency.map(&text),
+            original_price: self.original_price,
+            price_history: self.price_history,
+            size: self.size,
+            user_ratings: self.user_ratings,
+            recommendations: text(self.recommendations),
+            reviews: self.reviews.into_iter().map(&text).collect(),
+            review_stars: self.review_stars,
+            availability: self.availability,
+            extra: self
+                .extra
+                .into_iter()
+                .map(|(label, value)| (text(label), text(value)))
+                .collect(),
+        }
+    }
+}
+
+impl From<ProductRef<'_>> for Product {
+    fn from(product: ProductRef<'_>) -> Self {
+        Product {
+            product_id: product.product_id,
+            product_name: product.product_name.to_string(),
+            skin_type: product.skin_type.to_string(),
+            ingredients: product.ingredients.to_string(),
+            rating: product.rating,
+            price: product.price,
+            currency: product.currency.map(Currency::from_token),
+            original_price: product.original_price,
+            price_history: product.price_history,
+            size: product.size,
+            user_ratings: product.user_ratings,
+            recommendations: product.recommendations.to_string(),
+            reviews: product.reviews.into_iter().map(str::to_string).collect(),
+            review_stars: product.review_stars,
+            availability: product.availability,
+            extra: product
+                .extra
+                .into_iter()
+                .map(|(label, value)| (label.to_string(), value.to_string()))
+                .collect(),
+            allergens: Vec::new(),
+            inci: Vec::new(),
+            sentiment: None,
+            source_map: None,
+        }
+    }
+}
@@ -0,0 +1,142 @@
+use crate::numbers::NumberFormat;
+use crate::Rule;
+use pest::iterators::Pair;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Grams in an avoirdupois ounce
+const GRAMS_PER_OUNCE: f64 = 28.349523125;
+/// Millilitres in a US fluid ounce
+const MILLILITERS_PER_FLUID_OUNCE: f64 = 29.5735295625;
+
+/// Whether a quantity measures volume or mass
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Dimension {
+    Volume,
+    Mass,
+}
+
+impl Dimension {
+    /// The amount unit prices are given for, e.g. `100 ml`
+    pub fn unit_price_basis(&self) -> &'static str {
+        match self {
+            Dimension::Volume => "100 ml",
+            Dimension::Mass => "100 g",
+        }
+    }
+}
+
+/// A unit a product size is given in. Serialized as its symbol, e.g. `"fl oz"`
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unit {
+    #[serde(rename = "ml")]
+    Milliliter,
+    #[serde(rename = "l")]
+    Liter,
+    #[serde(rename = "g")]
+    Gram,
+    #[serde(rename = "kg")]
+    Kilogram,
+    #[serde(rename = "oz")]
+    Ounce,
+    #[serde(rename = "fl oz")]
+    FluidOunce,
+}
+
+impl Unit {
+    /// Reads a unit as the `unit` rule accepts it, e.g. `ML`, `fl. oz` or `мл`
+    pub fn parse(token: &str) -> Option<Self> {
+        match token.to_lowercase().as_str() {
+            "ml" | "мл" => Some(Unit::Milliliter),
+            "l" | "л" => Some(Unit::Liter),
+            "g" | "г" => Some(Unit::Gram),
+            "kg" | "кг" => Some(Unit::Kilogram),
+            "oz" => Some(Unit::Ounce),
+            "fl oz" | "fl. oz" => Some(Unit::FluidOunce),
+            _ => None,
+        }
+    }
+
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Unit::Milliliter => "ml",
+            Unit::Liter => "l",
+            Unit::Gram => "g",
+            Unit::Kilogram => "kg",
+            Unit::Ounce => "oz",
+            Unit::FluidOunce => "fl oz",
+        }
+    }
+
+    pub fn dimension(&self) -> Dimension {
+        match self {
+            Unit::Milliliter | Unit::Liter | Unit::FluidOunce => Dimension::Volume,
+            Unit::Gram | Unit::Kilogram | Unit::Ounce => Dimension::Mass,
+        }
+    }
+
+    /// How many millilitres or grams one of this unit is
+    fn base_factor(&self) -> f64 {
+        match self {
+            Unit::Milliliter | Unit::Gram => 1.0,
+            Unit::Liter | Unit::Kilogram => 1000.0,
+            Unit::Ounce => GRAMS_PER_OUNCE,
+            Unit::FluidOunce => MILLILITERS_PER_FLUID_OUNCE,
+        }
+    }
+}
+
+impl fmt::Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.symbol())
+    }
+}
+
+/// The size of a product, e.g. 50 ml or 1.7 fl oz
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Quantity {
+    pub amount: f64,
+    pub unit: Unit,
+}
+
+impl Quantity {
+    /// Reads a `measure` pair, reading an ambiguous amount in the given format
+    pub fn from_pair(pair: Pair<Rule>, numbers: NumberFormat) -> Option<Self> {
+        let mut amount = None;
+        let mut unit = None;
+        for inner in pair.into_inner() {
+            match inner.as_rule() {
+                Rule::amount => amount = numbers.parse(inner.as_str()),
+                Rule::unit => unit = Unit::parse(inner.as_str()),
+                _ => {}
+            }
+        }
+        Some(Quantity {
+            amount: amount?,
+            unit: unit?,
+        })
+    }
+
+    pub fn dimension(&self) -> Dimension {
+        self.unit.dimension()
+    }
+
+    /// The quantity in millilitres for volumes and in grams for masses
+    pub fn base_amount(&self) -> f64 {
+        self.amount * self.unit.base_factor()
+    }
+
+    /// What `price` comes to per 100 ml or per 100 g of this quantity, or `None` for an empty
+    /// quantity
+    pub fn unit_price(&self, price: f64) -> Option<f64> {
+        let base = self.base_amount();
+        (base > 0.0).then(|| price / base * 100.0)
+    }
+}
+
+impl fmt::Display for Quantity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.amount, self.unit)
+    }
+}
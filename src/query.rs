@@ -0,0 +1,505 @@
+use crate::availability::{Availability, STATUSES};
+use crate::currency::Currency;
+use crate::Product;
+use pest::iterators::Pair;
+use pest::Parser;
+use pest_derive::Parser;
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+
+#[derive(Parser)]
+#[grammar = "./query.pest"]
+pub struct QueryGrammar;
+
+/// An error produced while parsing or type-checking a query
+#[derive(Error, Debug)]
+pub enum QueryError {
+    #[error("invalid query: {0}")]
+    Syntax(String),
+    #[error("unknown field `{0}`")]
+    UnknownField(String),
+    #[error("unknown availability status `{0}`, expected in_stock, out_of_stock, pre_order or discontinued")]
+    UnknownStatus(String),
+    #[error("operator `{op}` cannot compare field `{field}` with {literal}")]
+    Type {
+        field: Field,
+        op: Op,
+        literal: String,
+    },
+}
+
+/// A product field that can be filtered, sorted or projected on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    ProductId,
+    ProductName,
+    SkinType,
+    Ingredients,
+    Rating,
+    Price,
+    Currency,
+    UserRatings,
+    Recommendations,
+    Reviews,
+    Availability,
+    StockQuantity,
+    Allergens,
+    UnitPrice,
+    Sentiment,
+}
+
+/// The shape of a field's value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldKind {
+    Number,
+    Text,
+    Numbers,
+    Texts,
+    /// An availability, true when in stock and equal to its status tag
+    Status,
+}
+
+impl Field {
+    pub const ALL: [Field; 15] = [
+        Field::ProductId,
+        Field::ProductName,
+        Field::SkinType,
+        Field::Ingredients,
+        Field::Rating,
+        Field::Price,
+        Field::Currency,
+        Field::UserRatings,
+        Field::Recommendations,
+        Field::Reviews,
+        Field::Availability,
+        Field::StockQuantity,
+        Field::Allergens,
+        Field::UnitPrice,
+        Field::Sentiment,
+    ];
+
+    /// The field name as used in the JSON output; `unit_price` is computed from the price and size,
+    /// `stock_quantity` is the quantity on hand of the availability, and `sentiment` is the score
+    /// of the sentiment summary. Products without an id, size, stock quantity or sentiment have
+    /// no value for those fields
+    pub fn name(self) -> &'static str {
+        match self {
+            Field::ProductId => "product_id",
+            Field::ProductName => "product_name",
+            Field::SkinType => "skin_type",
+            Field::Ingredients => "ingredients",
+            Field::Rating => "rating",
+            Field::Price => "price",
+            Field::Currency => "currency",
+            Field::UserRatings => "user_ratings",
+            Field::Recommendations => "recommendations",
+            Field::Reviews => "reviews",
+            Field::Availability => "availability",
+            Field::StockQuantity => "stock_quantity",
+            Field::Allergens => "allergens",
+            Field::UnitPrice => "unit_price",
+            Field::Sentiment => "sentiment",
+        }
+    }
+
+    pub fn kind(self) -> FieldKind {
+        match self {
+            Field::ProductName
+            | Field::SkinType
+            | Field::Ingredients
+            | Field::Currency
+            | Field::Recommendations => FieldKind::Text,
+            Field::ProductId
+            | Field::Rating
+            | Field::Price
+            | Field::StockQuantity
+            | Field::UnitPrice
+            | Field::Sentiment => FieldKind::Number,
+            Field::UserRatings => FieldKind::Numbers,
+            Field::Reviews | Field::Allergens => FieldKind::Texts,
+            Field::Availability => FieldKind::Status,
+        }
+    }
+
+    /// Reads this field from a product
+    pub fn value(self, product: &Product) -> Value<'_> {
+        match self {
+            Field::ProductId => product.product_id.map_or(Value::Missing, Value::Integer),
+            Field::ProductName => Value::Text(&product.product_name),
+            Field::SkinType => Value::Text(&product.skin_type),
+            Field::Ingredients => Value::Text(&product.ingredients),
+            Field::Rating => Value::Number(product.rating),
+            Field::Price => Value::Number(product.price),
+            Field::Currency => Value::Text(product.currency.as_ref().map_or("", Currency::code)),
+            Field::UserRatings => Value::Numbers(&product.user_ratings),
+            Field::Recommendations => Value::Text(&product.recommendations),
+            Field::Reviews => Value::Texts(&product.reviews),
+            Field::Availability => Value::Availability(&product.availability),
+            Field::StockQuantity => product
+                .availability
+                .quantity()
+                .map_or(Value::Missing, Value::Integer),
+            Field::Allergens => Value::Texts(&product.allergens),
+            Field::UnitPrice => product.unit_price().map_or(Value::Missing, Value::Number),
+            Field::Sentiment => product
+                .sentiment
+                .as_ref()
+                .map_or(Value::Missing, |summary| Value::Number(summary.score)),
+        }
+    }
+}
+
+impl FromStr for Field {
+    type Err = QueryError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Field::ALL
+            .into_iter()
+            .find(|field| field.name() == s)
+            .ok_or_else(|| QueryError::UnknownField(s.to_string()))
+    }
+}
+
+impl fmt::Display for Field {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// A borrowed field value of a product
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Value<'a> {
+    Number(f64),
+    /// A whole number such as the product id, kept whole so it is output as an integer
+    Integer(u64),
+    Text(&'a str),
+    Numbers(&'a [f64]),
+    Texts(&'a [String]),
+    Availability(&'a Availability),
+    /// A computed field the product lacks the data for, such as the unit price of a product
+    /// without a size. It matches no comparison and sorts last
+    Missing,
+}
+
+impl Value<'_> {
+    /// Whether a bare field counts as matching: true, non-zero or non-empty
+    fn is_truthy(&self) -> bool {
+        match self {
+            Value::Number(n) => *n != 0.0,
+            Value::Integer(n) => *n != 0,
+            Value::Text(s) => !s.is_empty(),
+            Value::Numbers(list) => !list.is_empty(),
+            Value::Texts(list) => !list.is_empty(),
+            Value::Availability(availability) => availability.is_in_stock(),
+            Value::Missing => false,
+        }
+    }
+
+    /// Orders two values of the same field; lists are ordered by their length
+    fn compare(&self, other: &Value) -> Ordering {
+        match (self, other) {
+            (Value::Number(a), Value::Number(b)) => a.total_cmp(b),
+            (Value::Integer(a), Value::Integer(b)) => a.cmp(b),
+            (Value::Text(a), Value::Text(b)) => a.cmp(b),
+            (Value::Numbers(a), Value::Numbers(b)) => a.len().cmp(&b.len()),
+            (Value::Texts(a), Value::Texts(b)) => a.len().cmp(&b.len()),
+            (Value::Availability(a), Value::Availability(b)) => {
+                a.is_in_stock().cmp(&b.is_in_stock())
+            }
+            _ => Ordering::Equal,
+        }
+    }
+}
+
+/// A comparison operator
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    /// Case-insensitive substring match
+    Like,
+    /// Substring match for text, membership for lists
+    Contains,
+}
+
+impl fmt::Display for Op {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Op::Eq => "==",
+            Op::Ne => "!=",
+            Op::Lt => "<",
+            Op::Le => "<=",
+            Op::Gt => ">",
+            Op::Ge => ">=",
+            Op::Like => "~",
+            Op::Contains => "contains",
+        })
+    }
+}
+
+/// A literal on the right-hand side of a comparison
+#[derive(Debug, Clone, PartialEq)]
+pub enum Literal {
+    Number(f64),
+    Text(String),
+    /// `true` or `false`, the shorthand for comparing availability with in stock
+    Bool(bool),
+}
+
+impl fmt::Display for Literal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Literal::Number(n) => write!(f, "number {}", n),
+            Literal::Text(s) => write!(f, "string {:?}", s),
+            Literal::Bool(b) => write!(f, "boolean {}", b),
+        }
+    }
+}
+
+/// A parsed filter expression
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Or(Vec<Expr>),
+    And(Vec<Expr>),
+    Not(Box<Expr>),
+    /// A bare field, matching when it is true, non-zero or non-empty
+    Truthy(Field),
+    Compare(Field, Op, Literal),
+}
+
+impl Expr {
+    pub fn matches(&self, product: &Product) -> bool {
+        match self {
+            Expr::Or(exprs) => exprs.iter().any(|e| e.matches(product)),
+            Expr::And(exprs) => exprs.iter().all(|e| e.matches(product)),
+            Expr::Not(expr) => !expr.matches(product),
+            Expr::Truthy(field) => field.value(product).is_truthy(),
+            Expr::Compare(field, op, literal) => compare(field.value(product), *op, literal),
+        }
+    }
+}
+
+fn compare(value: Value, op: Op, literal: &Literal) -> bool {
+    let ordering = |ordering: Option<Ordering>| match (op, ordering) {
+        (Op::Eq, Some(o)) => o == Ordering::Equal,
+        (Op::Ne, Some(o)) => o != Ordering::Equal,
+        (Op::Lt, Some(o)) => o == Ordering::Less,
+        (Op::Le, Some(o)) => o != Ordering::Greater,
+        (Op::Gt, Some(o)) => o == Ordering::Greater,
+        (Op::Ge, Some(o)) => o != Ordering::Less,
+        _ => false,
+    };
+    let like = |text: &str, pattern: &str| text.to_lowercase().contains(&pattern.to_lowercase());
+
+    match (value, literal) {
+        (Value::Number(n), Literal::Number(l)) => ordering(n.partial_cmp(l)),
+        (Value::Integer(n), Literal::Number(l)) => ordering((n as f64).partial_cmp(l)),
+        (Value::Availability(a), Literal::Bool(l)) => ordering(Some(a.is_in_stock().cmp(l))),
+        (Value::Availability(a), Literal::Text(l)) => ordering(Some(a.status().cmp(l.as_str()))),
+        (Value::Text(text), Literal::Text(l)) => match op {
+            Op::Like => like(text, l),
+            Op::Contains => text.contains(l.as_str()),
+            _ => ordering(Some(text.cmp(l.as_str()))),
+        },
+        (Value::Numbers(list), Literal::Number(l)) => list.contains(l),
+        (Value::Texts(list), Literal::Text(l)) => match op {
+            Op::Like => list.iter().any(|text| like(text, l)),
+            _ => list.iter().any(|text| text.contains(l.as_str())),
+        },
+        _ => false,
+    }
+}
+
+/// Checks that `op` makes sense for the field and literal, so mistakes are reported
+/// when the query is parsed instead of silently matching nothing
+fn check_types(field: Field, op: Op, literal: &Literal) -> Result<(), QueryError> {
+    let ok = match (field.kind(), literal) {
+        (FieldKind::Number, Literal::Number(_)) => !matches!(op, Op::Like | Op::Contains),
+        (FieldKind::Text, Literal::Text(_)) => true,
+        (FieldKind::Numbers, Literal::Number(_)) => op == Op::Contains,
+        (FieldKind::Texts, Literal::Text(_)) => matches!(op, Op::Like | Op::Contains),
+        (FieldKind::Status, Literal::Text(status)) if matches!(op, Op::Eq | Op::Ne) => {
+            if !STATUSES.contains(&status.as_str()) {
+                return Err(QueryError::UnknownStatus(status.clone()));
+            }
+            true
+        }
+        (FieldKind::Status, Literal::Bool(_)) => matches!(op, Op::Eq | Op::Ne),
+        _ => false,
+    };
+    if ok {
+        Ok(())
+    } else {
+        Err(QueryError::Type {
+            field,
+            op,
+            literal: literal.to_string(),
+        })
+    }
+}
+
+fn build_expr(pair: Pair<Rule>) -> Result<Expr, QueryError> {
+    match pair.as_rule() {
+        Rule::or_expr | Rule::and_expr => {
+            let rule = pair.as_rule();
+            let mut exprs = pair
+                .into_inner()
+                .filter(|p| !matches!(p.as_rule(), Rule::or_op | Rule::and_op))
+                .map(build_expr)
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(match (exprs.len(), rule) {
+                (1, _) => exprs.remove(0),
+                (_, Rule::or_expr) => Expr::Or(exprs),
+                _ => Expr::And(exprs),
+            })
+        }
+        Rule::not_expr => {
+            let mut negations = 0;
+            let mut expr = None;
+            for inner in pair.into_inner() {
+                match inner.as_rule() {
+                    Rule::not_op => negations += 1,
+                    _ => expr = Some(build_expr(inner)?),
+                }
+            }
+            let mut expr = expr.ok_or_else(|| QueryError::Syntax(String::from("empty term")))?;
+            for _ in 0..negations {
+                expr = Expr::Not(Box::new(expr));
+            }
+            Ok(expr)
+        }
+        Rule::field => Ok(Expr::Truthy(pair.as_str().parse()?)),
+        Rule::comparison => {
+            let mut inner = pair.into_inner();
+            let (Some(field), Some(op), Some(literal)) = (inner.next(), inner.next(), inner.next())
+            else {
+                return Err(QueryError::Syntax(String::from("incomplete comparison")));
+            };
+            let field: Field = field.as_str().parse()?;
+            let op = match op.as_str() {
+                "==" | "=" => Op::Eq,
+                "!=" => Op::Ne,
+                "<" => Op::Lt,
+                "<=" => Op::Le,
+                ">" => Op::Gt,
+                ">=" => Op::Ge,
+                "~" => Op::Like,
+                _ => Op::Contains,
+            };
+            let literal =
+                match literal.as_rule() {
+                    Rule::number => Literal::Number(literal.as_str().parse().map_err(|_| {
+                        QueryError::Syntax(format!("bad number {}", literal.as_str()))
+                    })?),
+                    Rule::boolean => Literal::Bool(literal.as_str() == "true"),
+                    _ => Literal::Text(unescape(
+                        literal.into_inner().next().map_or("", |p| p.as_str()),
+                    )),
+                };
+            check_types(field, op, &literal)?;
+            Ok(Expr::Compare(field, op, literal))
+        }
+        rule => Err(QueryError::Syntax(format!("unexpected {:?}", rule))),
+    }
+}
+
+fn unescape(s: &str) -> String {
+    let mut result = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            if let Some(escaped) = chars.next() {
+                result.push(escaped);
+            }
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
+
+/// A parsed filter expression, e.g. `price < 300 and availability and skin_type ~ "oily"`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Query {
+    pub expr: Expr,
+}
+
+impl Query {
+    pub fn parse(input: &str) -> Result<Self, QueryError> {
+        let pair = QueryGrammar::parse(Rule::query, input)
+            .map_err(|e| QueryError::Syntax(e.to_string()))?
+            .next()
+            .and_then(|query| query.into_inner().next())
+            .ok_or_else(|| QueryError::Syntax(String::from("empty query")))?;
+
+        Ok(Query {
+            expr: build_expr(pair)?,
+        })
+    }
+
+    pub fn matches(&self, product: &Product) -> bool {
+        self.expr.matches(product)
+    }
+
+    /// Returns the products matching this query, in their original order
+    pub fn filter<'a>(&self, products: &'a [Product]) -> Vec<&'a Product> {
+        products.iter().filter(|p| self.matches(p)).collect()
+    }
+}
+
+impl FromStr for Query {
+    type Err = QueryError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Query::parse(s)
+    }
+}
+
+/// Sort direction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortOrder {
+    Asc,
+    Desc,
+}
+
+/// Sorts products by a field. The sort is stable, so products with equal values keep their order.
+/// Products missing the field come last in either order
+pub fn sort_products(products: &mut [&Product], field: Field, order: SortOrder) {
+    products.sort_by(|a, b| {
+        let (a, b) = (field.value(a), field.value(b));
+        match (a, b) {
+            (Value::Missing, Value::Missing) => Ordering::Equal,
+            (Value::Missing, _) => Ordering::Greater,
+            (_, Value::Missing) => Ordering::Less,
+            _ => match order {
+                SortOrder::Asc => a.compare(&b),
+                SortOrder::Desc => a.compare(&b).reverse(),
+            },
+        }
+    });
+}
+
+/// Keeps only the given fields of a product, in the given order
+pub fn project(product: &Product, fields: &[Field]) -> serde_json::Map<String, serde_json::Value> {
+    fields
+        .iter()
+        .map(|field| {
+            let value = match field.value(product) {
+                Value::Number(n) => serde_json::json!(n),
+                Value::Integer(n) => serde_json::json!(n),
+                Value::Text(s) => serde_json::json!(s),
+                Value::Numbers(list) => serde_json::json!(list),
+                Value::Texts(list) => serde_json::json!(list),
+                Value::Availability(availability) => serde_json::json!(availability),
+                Value::Missing => serde_json::Value::Null,
+            };
+            (field.name().to_string(), value)
+        })
+        .collect()
+}
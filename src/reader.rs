@@ -0,0 +1,96 @@
+use crate::{parse_product_with, ParseError, ParseOptions, Product, ProductBlock};
+use std::io::BufRead;
+
+/// Reads products one block at a time from any buffered reader, so only the current
+/// product block is held in memory. Blocks that fail to parse are yielded as errors and
+/// reading continues with the next block
+pub struct ProductReader<R: BufRead> {
+    reader: R,
+    options: ParseOptions,
+    block: String,
+    block_line: usize,
+    block_offset: usize,
+    line: String,
+    line_number: usize,
+    offset: usize,
+    done: bool,
+}
+
+impl<R: BufRead> ProductReader<R> {
+    pub fn new(reader: R) -> Self {
+        ProductReader::with_options(reader, ParseOptions::default())
+    }
+
+    /// Reads products as `options` say. An undetermined number format is taken from the first
+    /// block that reveals it and kept for the rest of the stream
+    pub fn with_options(reader: R, options: ParseOptions) -> Self {
+        ProductReader {
+            reader,
+            options,
+            block: String::new(),
+            block_line: 1,
+            block_offset: 0,
+            line: String::new(),
+            line_number: 0,
+            offset: 0,
+            done: false,
+        }
+    }
+
+    /// Parses the buffered block, if it holds anything besides whitespace
+    fn take_block(&mut self) -> Option<Result<Product, ParseError>> {
+        let result = if self.block.trim().is_empty() {
+            None
+        } else {
+            let block = ProductBlock {
+                line: self.block_line,
+                offset: self.block_offset,
+                text: &self.block,
+            };
+            if self.options.numbers.is_none() {
+                self.options = self.options.detect(&[block]);
+            }
+            Some(parse_product_with(&block, &self.options))
+        };
+        self.block.clear();
+        result
+    }
+}
+
+impl<R: BufRead> Iterator for ProductReader<R> {
+    type Item = Result<Product, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            self.line.clear();
+            match self.reader.read_line(&mut self.line) {
+                Ok(0) => {
+                    self.done = true;
+                    return self.take_block();
+                }
+                Ok(_) => {
+                    self.line_number += 1;
+                    let result = if self.options.labels.is_product_label(&self.line) {
+                        self.take_block()
+                    } else {
+                        None
+                    };
+                    if self.block.is_empty() {
+                        self.block_line = self.line_number;
+                        self.block_offset = self.offset;
+                    }
+                    self.block.push_str(&self.line);
+                    self.offset += self.line.len();
+                    if result.is_some() {
+                        return result;
+                    }
+                }
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(ParseError::Io(e)));
+                }
+            }
+        }
+        None
+    }
+}
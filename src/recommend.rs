@@ -0,0 +1,204 @@
+use crate::allergens::contains_words;
+use crate::currency::Currency;
+use crate::exchange::round_to;
+use crate::Product;
+use serde::Serialize;
+use std::fmt;
+
+/// How well a product made for all skin types suits a requested skin type, against 1.0 for a
+/// product made for that skin type
+const ALL_SKIN_TYPES: f64 = 0.8;
+
+/// Words of a skin type that do not name one, as in `Normal to Dry Skin` or `All Skin Types`
+const FILLER_WORDS: [&str; 5] = ["skin", "skins", "type", "types", "to"];
+
+/// How much each part of the score counts. The weights of the default add up to 1, so scores
+/// range from 0 to 1
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+pub struct Weights {
+    pub skin_type: f64,
+    pub rating: f64,
+    pub user_ratings: f64,
+    pub price: f64,
+}
+
+impl Default for Weights {
+    fn default() -> Self {
+        Weights {
+            skin_type: 0.4,
+            rating: 0.3,
+            user_ratings: 0.15,
+            price: 0.15,
+        }
+    }
+}
+
+/// What a shopper is looking for
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Preferences {
+    pub skin_type: String,
+    /// The highest price to recommend, in the currency of the catalog prices
+    pub budget: Option<f64>,
+    /// Whether to leave out products that are not in stock
+    pub in_stock: bool,
+    /// Ingredients to avoid, matched as whole words, so `Oil` leaves out `Coconut Oil`
+    pub exclude: Vec<String>,
+    pub weights: Weights,
+}
+
+impl Preferences {
+    pub fn new(skin_type: &str) -> Self {
+        Preferences {
+            skin_type: skin_type.to_string(),
+            ..Preferences::default()
+        }
+    }
+}
+
+/// The weighted parts of a score, which add up to it
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+pub struct ScoreBreakdown {
+    /// How well the product suits the skin type
+    pub skin_type: f64,
+    /// The product rating out of 5
+    pub rating: f64,
+    /// How many user ratings the product has, against the most rated candidate
+    pub user_ratings: f64,
+    /// How cheap the product is, from 0 for the dearest candidate up to 1 for a free one
+    pub price: f64,
+}
+
+/// A recommended product with its score
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Recommendation {
+    pub product_id: Option<u64>,
+    pub product_name: String,
+    pub skin_type: String,
+    pub price: f64,
+    pub currency: Option<Currency>,
+    pub score: f64,
+    pub breakdown: ScoreBreakdown,
+}
+
+/// The skin types named in a description such as `Oily/Combination Skin`, in lowercase
+fn skin_types(description: &str) -> Vec<String> {
+    description
+        .split(|c: char| !c.is_alphanumeric())
+        .map(str::to_lowercase)
+        .filter(|word| !word.is_empty() && !FILLER_WORDS.contains(&word.as_str()))
+        .collect()
+}
+
+/// How well a product for `product` skin suits `wanted` skin: 1.0 when it names the skin type,
+/// less for products for all skin types and 0.0 for products for other skin types
+pub fn skin_match(product: &str, wanted: &str) -> f64 {
+    let product = skin_types(product);
+    let wanted = skin_types(wanted);
+    if wanted.iter().any(|skin| product.contains(skin)) {
+        1.0
+    } else if product.iter().any(|skin| skin == "all") {
+        ALL_SKIN_TYPES
+    } else {
+        0.0
+    }
+}
+
+/// Ranks the products that suit `preferences`, best first. Products for other skin types, over
+/// the budget, out of stock when stock is required or with an excluded ingredient are left out.
+/// Prices are compared as they are, so convert a catalog with mixed currencies first
+pub fn recommend(products: &[Product], preferences: &Preferences) -> Vec<Recommendation> {
+    let candidates: Vec<(&Product, f64)> = products
+        .iter()
+        .map(|product| {
+            (
+                product,
+                skin_match(&product.skin_type, &preferences.skin_type),
+            )
+        })
+        .filter(|(_, skin)| *skin > 0.0)
+        .filter(|(product, _)| {
+            preferences
+                .budget
+                .is_none_or(|budget| product.price <= budget)
+        })
+        .filter(|(product, _)| !preferences.in_stock || product.availability.is_in_stock())
+        .filter(|(product, _)| {
+            !product.ingredients.split(',').any(|ingredient| {
+                preferences
+                    .exclude
+                    .iter()
+                    .any(|excluded| contains_words(ingredient, excluded))
+            })
+        })
+        .collect();
+
+    let most_rated = candidates
+        .iter()
+        .map(|(product, _)| product.user_ratings.len())
+        .max()
+        .unwrap_or(0);
+    let dearest = candidates
+        .iter()
+        .map(|(product, _)| product.price)
+        .fold(f64::NEG_INFINITY, f64::max);
+
+    let weights = preferences.weights;
+    let mut recommendations: Vec<Recommendation> = candidates
+        .into_iter()
+        .map(|(product, skin)| {
+            let user_ratings = match most_rated {
+                0 => 0.0,
+                most => product.user_ratings.len() as f64 / most as f64,
+            };
+            let price = if dearest > 0.0 {
+                1.0 - product.price / dearest
+            } else {
+                1.0
+            };
+            let breakdown = ScoreBreakdown {
+                skin_type: round_to(weights.skin_type * skin, 3),
+                rating: round_to(weights.rating * (product.rating / 5.0).clamp(0.0, 1.0), 3),
+                user_ratings: round_to(weights.user_ratings * user_ratings, 3),
+                price: round_to(weights.price * price, 3),
+            };
+            Recommendation {
+                product_id: product.product_id,
+                product_name: product.product_name.clone(),
+                skin_type: product.skin_type.clone(),
+                price: product.price,
+                currency: product.currency.clone(),
+                score: round_to(
+                    breakdown.skin_type
+                        + breakdown.rating
+                        + breakdown.user_ratings
+                        + breakdown.price,
+                    3,
+                ),
+                breakdown,
+            }
+        })
+        .collect();
+    recommendations.sort_by(|a, b| b.score.total_cmp(&a.score));
+    recommendations
+}
+
+impl fmt::Display for Recommendation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let price = match &self.currency {
+            Some(currency) => currency.format(self.price),
+            None => self.price.to_string(),
+        };
+        write!(
+            f,
+            "{:.3}  {} ({}, {}): skin type {:.3} + rating {:.3} + user ratings {:.3} + price {:.3}",
+            self.score,
+            self.product_name,
+            self.skin_type,
+            price,
+            self.breakdown.skin_type,
+            self.breakdown.rating,
+            self.breakdown.user_ratings,
+            self.breakdown.price
+        )
+    }
+}
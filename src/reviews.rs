@@ -0,0 +1,173 @@
+use crate::exchange::round_to;
+use crate::{Product, Rule};
+use pest::iterators::Pair;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use thiserror::Error;
+
+/// How far apart, in stars, the average review stars and the average user rating may be before
+/// they disagree
+const AVERAGE_GAP: f64 = 1.0;
+
+/// An error in the star rating of a review
+#[derive(Error, Debug, PartialEq)]
+pub enum ReviewError {
+    #[error("a review cannot be rated out of {0} stars")]
+    Scale(f64),
+    #[error("a review rated out of {out_of} cannot give {stars} stars")]
+    OutOfRange { stars: f64, out_of: f64 },
+}
+
+/// The star rating of a review, such as `(4/5)`; `review` is the 0-based position of the
+/// review in the `reviews` list
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct ReviewStars {
+    pub review: usize,
+    pub stars: f64,
+    pub out_of: f64,
+}
+
+impl ReviewStars {
+    /// Reads a `review_stars` pair of the review at position `review`
+    pub fn from_pair(pair: Pair<Rule>, review: usize) -> Result<Self, ReviewError> {
+        let mut numbers = pair
+            .into_inner()
+            .filter(|p| p.as_rule() == Rule::number)
+            .filter_map(|p| p.as_str().parse::<f64>().ok());
+        let stars = numbers.next().unwrap_or(0.0);
+        let out_of = numbers.next().unwrap_or(0.0);
+        if out_of <= 0.0 {
+            return Err(ReviewError::Scale(out_of));
+        }
+        if !(0.0..=out_of).contains(&stars) {
+            return Err(ReviewError::OutOfRange { stars, out_of });
+        }
+        Ok(ReviewStars {
+            review,
+            stars,
+            out_of,
+        })
+    }
+
+    /// The stars on the five-star scale of `user_ratings`, so `(8/10)` is 4
+    pub fn out_of_five(&self) -> f64 {
+        round_to(self.stars / self.out_of * 5.0, 2)
+    }
+}
+
+/// How the review stars of one product compare with its user ratings
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct RatingCheck {
+    pub product_id: Option<u64>,
+    pub product_name: String,
+    /// The average review stars, out of five
+    pub review_average: f64,
+    /// The average user rating, `None` without user ratings
+    pub user_average: Option<f64>,
+    /// Review stars that no user rating matches, once every user rating has been matched to
+    /// at most one review
+    pub unmatched: Vec<ReviewStars>,
+}
+
+impl RatingCheck {
+    /// Whether some review stars have no user rating to go with them, or the averages are
+    /// `AVERAGE_GAP` or more stars apart
+    pub fn disagrees(&self) -> bool {
+        !self.unmatched.is_empty()
+            || self
+                .user_average
+                .is_some_and(|user| (user - self.review_average).abs() >= AVERAGE_GAP)
+    }
+}
+
+/// Compares the review stars of a product with its user ratings. `None` when no review has stars
+pub fn check_stars(product: &Product) -> Option<RatingCheck> {
+    if product.review_stars.is_empty() {
+        return None;
+    }
+    let average = |values: &[f64]| round_to(values.iter().sum::<f64>() / values.len() as f64, 2);
+
+    let stars: Vec<f64> = product
+        .review_stars
+        .iter()
+        .map(ReviewStars::out_of_five)
+        .collect();
+    let mut unused = product.user_ratings.clone();
+    let mut unmatched = Vec::new();
+    for review in &product.review_stars {
+        let stars = review.out_of_five();
+        match unused
+            .iter()
+            .position(|rating| (rating - stars).abs() < 0.01)
+        {
+            Some(i) => {
+                unused.swap_remove(i);
+            }
+            None => unmatched.push(*review),
+        }
+    }
+
+    Some(RatingCheck {
+        product_id: product.product_id,
+        product_name: product.product_name.clone(),
+        review_average: average(&stars),
+        user_average: (!product.user_ratings.is_empty()).then(|| average(&product.user_ratings)),
+        unmatched,
+    })
+}
+
+/// Which products have review stars that disagree with their user ratings
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct RatingReport {
+    /// How many products have reviews with stars
+    pub checked: usize,
+    /// The products whose review stars disagree with their user ratings, in catalog order
+    pub products: Vec<RatingCheck>,
+}
+
+/// Checks every product with review stars, listing the ones that disagree
+pub fn report(products: &[Product]) -> RatingReport {
+    let checks: Vec<RatingCheck> = products.iter().filter_map(check_stars).collect();
+    RatingReport {
+        checked: checks.len(),
+        products: checks.into_iter().filter(RatingCheck::disagrees).collect(),
+    }
+}
+
+impl fmt::Display for RatingReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for check in &self.products {
+            match check.product_id {
+                Some(id) => writeln!(f, "Product {}: {}", id, check.product_name)?,
+                None => writeln!(f, "{}", check.product_name)?,
+            }
+            match check.user_average {
+                Some(user) => writeln!(
+                    f,
+                    "  reviews average {:.2} stars, user ratings average {:.2}",
+                    check.review_average, user
+                )?,
+                None => writeln!(
+                    f,
+                    "  reviews average {:.2} stars, but there are no user ratings",
+                    check.review_average
+                )?,
+            }
+            for review in &check.unmatched {
+                writeln!(
+                    f,
+                    "  review {} gives {}/{} stars, which no user rating matches",
+                    review.review + 1,
+                    review.stars,
+                    review.out_of
+                )?;
+            }
+        }
+        writeln!(
+            f,
+            "{} of {} products with review stars disagree with their user ratings",
+            self.products.len(),
+            self.checked
+        )
+    }
+}
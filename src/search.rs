@@ -0,0 +1,185 @@
+use crate::Product;
+use std::collections::BTreeMap;
+use std::ops::Bound;
+
+/// BM25 term frequency saturation
+const K1: f64 = 1.2;
+/// BM25 document length normalization
+const B: f64 = 0.75;
+/// How much a term found by prefix counts, against 1.0 for the exact term
+const PREFIX_WEIGHT: f64 = 0.5;
+/// Query terms shorter than this only match whole terms, so `a` does not match every word
+/// starting with it
+const MIN_PREFIX: usize = 3;
+
+/// Letters with diacritics and the letters they fold to
+const DIACRITICS: [(&str, char); 20] = [
+    ("àáâãäåāăą", 'a'),
+    ("çćĉċč", 'c'),
+    ("ďđ", 'd'),
+    ("èéêëēĕėęě", 'e'),
+    ("ĝğġģ", 'g'),
+    ("ìíîïĩīĭįı", 'i'),
+    ("ķ", 'k'),
+    ("ĺļľŀł", 'l'),
+    ("ñńņňŉ", 'n'),
+    ("òóôõöøōŏő", 'o'),
+    ("ŕŗř", 'r'),
+    ("śŝşš", 's'),
+    ("ţťŧ", 't'),
+    ("ùúûüũūŭůűų", 'u'),
+    ("ýÿŷ", 'y'),
+    ("źżž", 'z'),
+    ("й", 'и'),
+    ("ї", 'і'),
+    ("ё", 'е'),
+    ("ґ", 'г'),
+];
+
+/// Lowercases text and strips diacritics, so `Crème` and `creme` are the same word
+pub fn fold(text: &str) -> String {
+    let mut folded = String::with_capacity(text.len());
+    for c in text.chars().flat_map(char::to_lowercase) {
+        match c {
+            'ß' => folded.push_str("ss"),
+            'æ' => folded.push_str("ae"),
+            'œ' => folded.push_str("oe"),
+            c => folded.push(
+                DIACRITICS
+                    .iter()
+                    .find(|(from, _)| from.contains(c))
+                    .map_or(c, |(_, to)| *to),
+            ),
+        }
+    }
+    folded
+}
+
+/// Splits text into folded words
+pub fn tokenize(text: &str) -> Vec<String> {
+    fold(text)
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|token| !token.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// The text of a product that is searched, with how much a term in it counts
+fn fields(product: &Product) -> Vec<(&str, f64)> {
+    let mut fields = vec![
+        (product.product_name.as_str(), 2.0),
+        (product.skin_type.as_str(), 1.0),
+        (product.ingredients.as_str(), 1.0),
+        (product.recommendations.as_str(), 1.0),
+    ];
+    fields.extend(product.reviews.iter().map(|review| (review.as_str(), 1.0)));
+    fields
+}
+
+/// How often a term appears in one product, weighted by the fields it appears in
+#[derive(Debug, Clone, PartialEq)]
+struct Posting {
+    product: usize,
+    frequency: f64,
+}
+
+/// An inverted index over the name, skin type, ingredients, recommendations and reviews of
+/// products, ranking matches with BM25
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SearchIndex {
+    terms: BTreeMap<String, Vec<Posting>>,
+    lengths: Vec<f64>,
+    average_length: f64,
+}
+
+impl SearchIndex {
+    pub fn new(products: &[Product]) -> Self {
+        let mut terms: BTreeMap<String, Vec<Posting>> = BTreeMap::new();
+        let mut lengths = Vec::with_capacity(products.len());
+
+        for (index, product) in products.iter().enumerate() {
+            let mut frequencies: BTreeMap<String, f64> = BTreeMap::new();
+            let mut length = 0.0;
+            for (text, weight) in fields(product) {
+                for token in tokenize(text) {
+                    *frequencies.entry(token).or_default() += weight;
+                    length += weight;
+                }
+            }
+            for (term, frequency) in frequencies {
+                terms.entry(term).or_default().push(Posting {
+                    product: index,
+                    frequency,
+                });
+            }
+            lengths.push(length);
+        }
+
+        let average_length = if lengths.is_empty() {
+            0.0
+        } else {
+            lengths.iter().sum::<f64>() / lengths.len() as f64
+        };
+        SearchIndex {
+            terms,
+            lengths,
+            average_length,
+        }
+    }
+
+    /// How many products the index covers
+    pub fn len(&self) -> usize {
+        self.lengths.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lengths.is_empty()
+    }
+
+    /// The indexed terms a query term matches: itself, and with at least `MIN_PREFIX`
+    /// characters, every term it is a prefix of
+    fn expand<'a>(&'a self, token: &'a str) -> impl Iterator<Item = (&'a str, &'a [Posting])> {
+        let prefix = token.chars().count() >= MIN_PREFIX;
+        self.terms
+            .range::<str, _>((Bound::Included(token), Bound::Unbounded))
+            .take_while(move |(term, _)| {
+                term.as_str() == token || (prefix && term.starts_with(token))
+            })
+            .map(|(term, postings)| (term.as_str(), postings.as_slice()))
+    }
+
+    /// Products matching any term of `query`, as (position, score) pairs with the best match
+    /// first. Products matching more terms, rarer terms or matching them more often rank higher
+    pub fn search(&self, query: &str) -> Vec<(usize, f64)> {
+        let count = self.len() as f64;
+        let mut scores = vec![0.0; self.len()];
+
+        for token in tokenize(query) {
+            let mut best = vec![0.0f64; self.len()];
+            for (term, postings) in self.expand(&token) {
+                let weight = if term == token { 1.0 } else { PREFIX_WEIGHT };
+                let found = postings.len() as f64;
+                let idf = (1.0 + (count - found + 0.5) / (found + 0.5)).ln();
+                for posting in postings {
+                    let length = self.lengths[posting.product] / self.average_length;
+                    let frequency = posting.frequency;
+                    let score = idf * frequency * (K1 + 1.0)
+                        / (frequency + K1 * (1.0 - B + B * length))
+                        * weight;
+                    best[posting.product] = best[posting.product].max(score);
+                }
+            }
+            for (score, best) in scores.iter_mut().zip(best) {
+                *score += best;
+            }
+        }
+
+        let mut matches: Vec<(usize, f64)> = scores
+            .into_iter()
+            .enumerate()
+            .filter(|(_, score)| *score > 0.0)
+            .collect();
+        matches.sort_by(|a, b| b.1.total_cmp(&a.1));
+        matches
+    }
+}
@@ -0,0 +1,301 @@
+use crate::exchange::round_to;
+use crate::search::tokenize;
+use crate::Product;
+use serde::{Deserialize, Serialize};
+
+/// How many words before a term a negation such as `doesn't` or `without` reaches
+const NEGATION_WINDOW: usize = 4;
+/// What a negated term counts for: `not greasy` is good, but not as good as `greasy` is bad
+const NEGATION_FACTOR: f64 = -0.5;
+/// Words that negate the terms after them. Apostrophes split words, so `doesn't` is `doesn`
+const NEGATIONS: [&str; 18] = [
+    "no", "not", "never", "without", "nor", "hardly", "cannot", "doesn", "doesnt", "don", "dont",
+    "didn", "didnt", "isn", "isnt", "wasn", "aren", "won",
+];
+/// Words that end the reach of a negation, as in `not cheap but works`
+const CONTRASTS: [&str; 3] = ["but", "though", "however"];
+/// How many extra words may sit between the words of a term, so `smaller pores` is found in
+/// `pores look visibly smaller`
+const TERM_SLACK: usize = 2;
+/// How far apart, in stars, the headline rating and the rating the reviews suggest may be
+/// before the reviews contradict the rating
+const RATING_GAP: f64 = 2.0;
+
+/// A word or phrase that says something good or bad about a product, with the other ways
+/// reviews put it
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Term {
+    pub term: String,
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    /// Above zero for praise and below zero for complaints; strong words count 2
+    pub score: f64,
+    /// How the term reads as a keyword when negated, `not <term>` when not given
+    #[serde(default)]
+    pub negated: Option<String>,
+}
+
+impl Term {
+    pub fn new(term: &str, aliases: &[&str], score: f64) -> Self {
+        Term {
+            term: term.to_string(),
+            aliases: aliases.iter().map(|a| a.to_string()).collect(),
+            score,
+            negated: None,
+        }
+    }
+
+    /// Sets how the term reads when negated, such as `no white cast`
+    pub fn negated_as(mut self, keyword: &str) -> Self {
+        self.negated = Some(keyword.to_string());
+        self
+    }
+
+    /// The term as a keyword, negated or not
+    pub fn keyword(&self, negated: bool) -> String {
+        match (negated, &self.negated) {
+            (false, _) => self.term.clone(),
+            (true, Some(keyword)) => keyword.clone(),
+            (true, None) => format!("not {}", self.term),
+        }
+    }
+}
+
+/// A term found in a review
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Mention {
+    pub term: String,
+    /// The term as it is meant, such as `not greasy`
+    pub keyword: String,
+    pub negated: bool,
+    /// The score of the term, flipped and weakened when negated
+    pub score: f64,
+}
+
+/// The sentiment of one review
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ReviewSentiment {
+    /// From -1.0 for only complaints to 1.0 for only praise, 0.0 without any terms
+    pub score: f64,
+    pub mentions: Vec<Mention>,
+}
+
+/// A keyword of the reviews of a product and how many times it was mentioned
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Keyword {
+    pub keyword: String,
+    pub count: usize,
+    pub score: f64,
+}
+
+/// The sentiment of all reviews of a product
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SentimentSummary {
+    /// The average score of the reviews, from -1.0 to 1.0
+    pub score: f64,
+    pub positive: usize,
+    pub negative: usize,
+    pub neutral: usize,
+    /// The keywords of the reviews, most mentioned first
+    pub keywords: Vec<Keyword>,
+    /// Whether the reviews say something quite different from the headline `rating`
+    pub contradicts_rating: bool,
+}
+
+impl SentimentSummary {
+    /// The headline rating the reviews suggest, from 1.0 to 5.0
+    pub fn expected_rating(&self) -> f64 {
+        round_to(3.0 + 2.0 * self.score, 1)
+    }
+}
+
+/// The words and phrases that reviews are scored by. Custom lexicons are loaded from a TOML
+/// or JSON file with a `terms` list, which replaces the built-in one:
+///
+/// ```toml
+/// [[terms]]
+/// term = "greasy"
+/// aliases = ["oily residue", "sticky"]
+/// score = -1.0
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct Lexicon {
+    pub terms: Vec<Term>,
+}
+
+impl Lexicon {
+    /// Common praise and complaints from cosmetics reviews
+    pub fn builtin() -> Self {
+        Lexicon {
+            terms: vec![
+                Term::new("excellent", &["exceptional", "outstanding"], 2.0),
+                Term::new("amazing", &["awesome", "fantastic", "wonderful"], 2.0),
+                Term::new("love", &["loved", "loves"], 2.0),
+                Term::new("perfect", &["perfectly"], 2.0),
+                Term::new("great", &[], 1.0),
+                Term::new("good", &["nice", "pleasant"], 1.0),
+                Term::new("recommend", &["recommended"], 1.0),
+                Term::new("effective", &["works", "worked"], 1.0),
+                Term::new("soft", &["softer"], 1.0),
+                Term::new("smooth", &["smoother"], 1.0),
+                Term::new("bright", &["brighter", "radiant", "glowing"], 1.0),
+                Term::new("hydrating", &["hydrated", "hydrates", "moisturized"], 1.0),
+                Term::new("soothing", &["soothes", "calming"], 1.0),
+                Term::new("refreshing", &["refreshed"], 1.0),
+                Term::new("light", &["lightweight"], 1.0),
+                Term::new("absorbs quickly", &["absorbs fast"], 1.0),
+                Term::new("protection", &["protects"], 1.0).negated_as("no protection"),
+                Term::new("smaller pores", &["pores shrink"], 1.0).negated_as("no smaller pores"),
+                Term::new("reduces puffiness", &["less puffiness"], 1.0),
+                Term::new("greasy", &["oily residue", "sticky"], -1.0),
+                Term::new("white cast", &[], -1.0).negated_as("no white cast"),
+                Term::new("drying", &["dries out", "dried out", "dry patches"], -1.0),
+                Term::new("harsh", &["strong"], -1.0),
+                Term::new("sunburn", &["sunburned"], -1.0).negated_as("no sunburn"),
+                Term::new("redness", &[], -1.0).negated_as("no redness"),
+                Term::new("peeling", &["flaky"], -1.0).negated_as("no peeling"),
+                Term::new("clogged pores", &["clogs pores"], -1.0).negated_as("no clogged pores"),
+                Term::new("expensive", &["overpriced"], -1.0),
+                Term::new("bad", &["poor", "disappointing", "disappointed"], -1.0),
+                Term::new("no effect", &["does nothing", "did nothing"], -1.0)
+                    .negated_as("has an effect"),
+                Term::new("breakouts", &["breakout", "broke me out", "pimples"], -2.0)
+                    .negated_as("no breakouts"),
+                Term::new(
+                    "irritation",
+                    &["irritated", "irritating", "burning", "stings", "stinging"],
+                    -2.0,
+                )
+                .negated_as("no irritation"),
+                Term::new("rash", &[], -2.0).negated_as("no rash"),
+                Term::new("terrible", &["awful", "horrible", "worst"], -2.0),
+            ],
+        }
+    }
+
+    /// The terms in a review, in the order they appear. The words of a term are found in any
+    /// order with up to `TERM_SLACK` other words between them, and a negation shortly before a
+    /// term flips it, so `doesn't leave a white cast` counts for the product
+    pub fn analyze(&self, review: &str) -> ReviewSentiment {
+        let forms: Vec<(&Term, Vec<String>)> = self
+            .terms
+            .iter()
+            .flat_map(|term| {
+                std::iter::once(&term.term)
+                    .chain(&term.aliases)
+                    .map(move |form| (term, tokenize(form)))
+            })
+            .filter(|(_, words)| !words.is_empty())
+            .collect();
+
+        let mut mentions = Vec::new();
+        for clause in review.split(['.', ',', ';', '!', '?', ':']) {
+            let words = tokenize(clause);
+            let mut used = vec![false; words.len()];
+            for start in 0..words.len() {
+                if used[start] {
+                    continue;
+                }
+                // Longer forms come first, so `clogged pores` wins over a shorter form
+                let found = forms
+                    .iter()
+                    .filter(|(_, form)| form.contains(&words[start]))
+                    .filter_map(|(term, form)| {
+                        let end = (start + form.len() + TERM_SLACK).min(words.len());
+                        let window = &words[start..end];
+                        let mut positions = Vec::with_capacity(form.len());
+                        for word in form {
+                            let at = (0..window.len()).find(|&i| {
+                                window[i] == *word
+                                    && !used[start + i]
+                                    && !positions.contains(&(start + i))
+                            })?;
+                            positions.push(start + at);
+                        }
+                        Some((*term, form.len(), positions))
+                    })
+                    .max_by_key(|(_, length, _)| *length);
+                let Some((term, _, positions)) = found else {
+                    continue;
+                };
+                for position in positions {
+                    used[position] = true;
+                }
+
+                let negated = words[start.saturating_sub(NEGATION_WINDOW)..start]
+                    .iter()
+                    .rev()
+                    .take_while(|word| !CONTRASTS.contains(&word.as_str()))
+                    .any(|word| NEGATIONS.contains(&word.as_str()));
+                mentions.push(Mention {
+                    term: term.term.clone(),
+                    keyword: term.keyword(negated),
+                    negated,
+                    score: if negated {
+                        term.score * NEGATION_FACTOR
+                    } else {
+                        term.score
+                    },
+                });
+            }
+        }
+
+        let positive: f64 = mentions.iter().map(|m| m.score.max(0.0)).sum();
+        let negative: f64 = mentions.iter().map(|m| (-m.score).max(0.0)).sum();
+        let score = if positive + negative > 0.0 {
+            round_to((positive - negative) / (positive + negative), 3)
+        } else {
+            0.0
+        };
+        ReviewSentiment { score, mentions }
+    }
+
+    /// Scores every review of a product and checks the result against its headline rating.
+    /// `None` for products without reviews
+    pub fn summarize(&self, product: &Product) -> Option<SentimentSummary> {
+        if product.reviews.is_empty() {
+            return None;
+        }
+        let reviews: Vec<ReviewSentiment> = product
+            .reviews
+            .iter()
+            .map(|review| self.analyze(review))
+            .collect();
+
+        let mut keywords: Vec<Keyword> = Vec::new();
+        for mention in reviews.iter().flat_map(|r| &r.mentions) {
+            match keywords.iter_mut().find(|k| k.keyword == mention.keyword) {
+                Some(existing) => existing.count += 1,
+                None => keywords.push(Keyword {
+                    keyword: mention.keyword.clone(),
+                    count: 1,
+                    score: mention.score,
+                }),
+            }
+        }
+        keywords.sort_by_key(|k| std::cmp::Reverse(k.count));
+
+        let score = round_to(
+            reviews.iter().map(|r| r.score).sum::<f64>() / reviews.len() as f64,
+            3,
+        );
+        let mut summary = SentimentSummary {
+            score,
+            positive: reviews.iter().filter(|r| r.score > 0.0).count(),
+            negative: reviews.iter().filter(|r| r.score < 0.0).count(),
+            neutral: reviews.iter().filter(|r| r.score == 0.0).count(),
+            keywords,
+            contradicts_rating: false,
+        };
+        summary.contradicts_rating = summary.neutral < reviews.len()
+            && (product.rating - summary.expected_rating()).abs() >= RATING_GAP;
+        Some(summary)
+    }
+
+    /// Fills in the `sentiment` field of every product
+    pub fn annotate(&self, products: &mut [Product]) {
+        for product in products {
+            product.sentiment = self.summarize(product);
+        }
+    }
+}
@@ -0,0 +1,155 @@
+use crate::labels::Normalized;
+use crate::Rule;
+use pest::iterators::Pair;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// A location in the catalog source: a byte range plus the 1-based line and column it starts at
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct SourceSpan {
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl fmt::Display for SourceSpan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+impl SourceSpan {
+    /// Builds the span of `pair` with surrounding whitespace and newlines left out
+    fn trimmed(pair: &Pair<Rule>) -> Self {
+        let text = pair.as_str();
+        let leading = &text[..text.len() - text.trim_start().len()];
+        let start = pair.as_span().start() + leading.len();
+
+        let (mut line, mut column) = pair.line_col();
+        match leading.rfind('\n') {
+            Some(index) => {
+                line += leading.matches('\n').count();
+                column = leading[index + 1..].chars().count() + 1;
+            }
+            None => column += leading.chars().count(),
+        }
+
+        SourceSpan {
+            start,
+            end: start + text.trim().len(),
+            line,
+            column,
+        }
+    }
+}
+
+/// Source locations of a product, each of its fields (keyed by the field name used in the JSON
+/// output), each of its reviews and each of its extra fields (keyed by label)
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SourceMap {
+    pub product: SourceSpan,
+    pub fields: BTreeMap<String, SourceSpan>,
+    pub reviews: Vec<SourceSpan>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub extra: BTreeMap<String, SourceSpan>,
+}
+
+impl SourceMap {
+    /// Collects the spans of a `product` pair. Offsets, lines and columns are relative to the
+    /// text the pair was parsed from
+    pub fn from_pair(pair: &Pair<Rule>) -> Self {
+        let mut fields = BTreeMap::new();
+        let mut reviews = Vec::new();
+        let mut extra = BTreeMap::new();
+
+        for field in pair.clone().into_inner() {
+            let values: &[(&str, Rule)] = match field.as_rule() {
+                Rule::product_name => &[
+                    ("product_id", Rule::product_id),
+                    ("product_name", Rule::any_text),
+                ],
+                Rule::skin_type => &[("skin_type", Rule::any_text)],
+                Rule::ingredients => &[("ingredients", Rule::any_text)],
+                Rule::rating => &[("rating", Rule::amount)],
+                Rule::price => &[
+                    ("price", Rule::amount),
+                    ("currency", Rule::currency),
+                    ("original_price", Rule::discount),
+                ],
+                Rule::price_history => &[("price_history", Rule::price_points)],
+                Rule::size => &[("size", Rule::measure)],
+                Rule::user_ratings => &[("user_ratings", Rule::number_list)],
+                Rule::recommendations => &[("recommendations", Rule::any_text)],
+                Rule::availability => &[("availability", Rule::stock_status)],
+                Rule::reviews => {
+                    fields.insert(String::from("reviews"), SourceSpan::trimmed(&field));
+                    reviews.extend(
+                        field
+                            .into_inner()
+                            .filter(|p| p.as_rule() == Rule::review)
+                            .map(|p| SourceSpan::trimmed(&p)),
+                    );
+                    continue;
+                }
+                Rule::extra_field => {
+                    let mut inner = field.into_inner();
+                    let label = inner.clone().find(|p| p.as_rule() == Rule::extra_label);
+                    let value = inner.find(|p| p.as_rule() == Rule::any_text);
+                    if let (Some(label), Some(value)) = (label, value) {
+                        extra.insert(
+                            label.as_str().trim().to_string(),
+                            SourceSpan::trimmed(&value),
+                        );
+                    }
+                    continue;
+                }
+                _ => continue,
+            };
+
+            for (name, rule) in values {
+                if let Some(value) = field.clone().into_inner().find(|p| p.as_rule() == *rule) {
+                    fields.insert(name.to_string(), SourceSpan::trimmed(&value));
+                }
+            }
+        }
+
+        SourceMap {
+            product: SourceSpan::trimmed(pair),
+            fields,
+            reviews,
+            extra,
+        }
+    }
+
+    /// Moves every span by `offset` bytes and `lines` lines, for pairs parsed from a block
+    /// that starts `offset` bytes and `lines` lines into the whole input
+    pub fn shift(&mut self, offset: usize, lines: usize) {
+        for span in self.spans_mut() {
+            span.start += offset;
+            span.end += offset;
+            span.line += lines;
+        }
+    }
+
+    /// Moves every span of a pair parsed from `text` back to where it is in the text as written,
+    /// before its labels were normalized
+    pub fn restore(&mut self, text: &Normalized) {
+        for span in self.spans_mut() {
+            text.restore(span);
+        }
+    }
+
+    fn spans_mut(&mut self) -> impl Iterator<Item = &mut SourceSpan> {
+        std::iter::once(&mut self.product)
+            .chain(self.fields.values_mut())
+            .chain(self.reviews.iter_mut())
+            .chain(self.extra.values_mut())
+    }
+
+    /// The span of the field with the given JSON name, e.g. `"price"`
+    pub fn field(&self, name: &str) -> Option<&SourceSpan> {
+        self.fields.get(name)
+    }
+}
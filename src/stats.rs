@@ -0,0 +1,305 @@
+use crate::availability::Availability;
+use crate::currency::Currency;
+use crate::sentiment::{Lexicon, SentimentSummary};
+use crate::Product;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+/// Currency key used for products whose price has no currency
+const NO_CURRENCY: &str = "-";
+
+/// Price figures for the products priced in one currency
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct PriceStats {
+    pub count: usize,
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    pub median: f64,
+}
+
+impl PriceStats {
+    fn from_prices(mut prices: Vec<f64>) -> Self {
+        prices.sort_by(f64::total_cmp);
+        let count = prices.len();
+        let median = if count % 2 == 0 {
+            (prices[count / 2 - 1] + prices[count / 2]) / 2.0
+        } else {
+            prices[count / 2]
+        };
+
+        PriceStats {
+            count,
+            min: prices[0],
+            max: prices[count - 1],
+            mean: prices.iter().sum::<f64>() / count as f64,
+            median,
+        }
+    }
+}
+
+/// A value together with how many products (or ratings) have it
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Count {
+    pub name: String,
+    pub count: usize,
+}
+
+/// Totals and per-product averages of a list field such as `user_ratings` or `reviews`
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ListStats {
+    pub total: usize,
+    pub average_per_product: f64,
+}
+
+/// The review sentiment of one product
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ProductSentiment {
+    pub product_id: Option<u64>,
+    pub product_name: String,
+    pub rating: f64,
+    pub sentiment: SentimentSummary,
+}
+
+/// A summary of a parsed catalog
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct CatalogStats {
+    pub products: usize,
+    pub in_stock: usize,
+    pub out_of_stock: usize,
+    pub pre_order: usize,
+    pub discontinued: usize,
+    /// Price figures keyed by currency code; `-` holds prices without a currency
+    pub prices: BTreeMap<String, PriceStats>,
+    /// How many products have a headline `rating` in each half-star bucket
+    pub rating_distribution: Vec<Count>,
+    /// How often each individual user rating was given
+    pub user_rating_distribution: Vec<Count>,
+    pub user_ratings: ListStats,
+    pub reviews: ListStats,
+    /// The most common ingredients, most frequent first
+    pub top_ingredients: Vec<Count>,
+    /// Number of products per skin type, most frequent first
+    pub skin_types: Vec<Count>,
+    /// The review sentiment of every product with reviews, in catalog order
+    pub review_sentiment: Vec<ProductSentiment>,
+    /// The most mentioned review keywords, most frequent first
+    pub top_keywords: Vec<Count>,
+}
+
+/// Counts values case-insensitively, reporting each under its first spelling, most frequent first
+fn count_values<'a>(values: impl Iterator<Item = &'a str>) -> Vec<Count> {
+    let mut counts: Vec<Count> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+
+    for value in values.map(str::trim).filter(|v| !v.is_empty()) {
+        match index.get(&value.to_lowercase()) {
+            Some(&i) => counts[i].count += 1,
+            None => {
+                index.insert(value.to_lowercase(), counts.len());
+                counts.push(Count {
+                    name: value.to_string(),
+                    count: 1,
+                });
+            }
+        }
+    }
+    counts.sort_by_key(|c| std::cmp::Reverse(c.count));
+    counts
+}
+
+fn list_stats(total: usize, products: usize) -> ListStats {
+    ListStats {
+        total,
+        average_per_product: if products == 0 {
+            0.0
+        } else {
+            total as f64 / products as f64
+        },
+    }
+}
+
+impl CatalogStats {
+    /// Summarizes `products`, keeping at most `top` entries in the ingredient and keyword lists
+    pub fn new(products: &[Product], top: usize) -> Self {
+        CatalogStats::with_lexicon(products, top, &Lexicon::builtin())
+    }
+
+    /// Summarizes `products`, scoring reviews with a custom lexicon
+    pub fn with_lexicon(products: &[Product], top: usize, lexicon: &Lexicon) -> Self {
+        let status = |matches: fn(&Availability) -> bool| {
+            products.iter().filter(|p| matches(&p.availability)).count()
+        };
+
+        let mut prices: BTreeMap<String, Vec<f64>> = BTreeMap::new();
+        for product in products {
+            let currency = product
+                .currency
+                .as_ref()
+                .map_or(NO_CURRENCY, Currency::code);
+            prices
+                .entry(currency.to_string())
+                .or_default()
+                .push(product.price);
+        }
+
+        let mut buckets: BTreeMap<u32, usize> = BTreeMap::new();
+        for product in products {
+            let bucket = ((product.rating * 2.0).floor() as u32).min(9);
+            *buckets.entry(bucket).or_default() += 1;
+        }
+        let rating_distribution = buckets
+            .into_iter()
+            .map(|(bucket, count)| Count {
+                name: format!(
+                    "{:.1}-{:.1}",
+                    bucket as f64 / 2.0,
+                    (bucket + 1) as f64 / 2.0
+                ),
+                count,
+            })
+            .collect();
+
+        let mut stars: BTreeMap<String, usize> = BTreeMap::new();
+        for rating in products.iter().flat_map(|p| &p.user_ratings) {
+            *stars.entry(rating.to_string()).or_default() += 1;
+        }
+        let user_rating_distribution = stars
+            .into_iter()
+            .map(|(name, count)| Count { name, count })
+            .collect();
+
+        let mut top_ingredients =
+            count_values(products.iter().flat_map(|p| p.ingredients.split(',')));
+        top_ingredients.truncate(top);
+
+        let review_sentiment: Vec<ProductSentiment> = products
+            .iter()
+            .filter_map(|product| {
+                Some(ProductSentiment {
+                    product_id: product.product_id,
+                    product_name: product.product_name.clone(),
+                    rating: product.rating,
+                    sentiment: lexicon.summarize(product)?,
+                })
+            })
+            .collect();
+        let mut top_keywords = Vec::new();
+        for keyword in review_sentiment.iter().flat_map(|p| &p.sentiment.keywords) {
+            match top_keywords
+                .iter_mut()
+                .find(|c: &&mut Count| c.name == keyword.keyword)
+            {
+                Some(count) => count.count += keyword.count,
+                None => top_keywords.push(Count {
+                    name: keyword.keyword.clone(),
+                    count: keyword.count,
+                }),
+            }
+        }
+        top_keywords.sort_by_key(|c| std::cmp::Reverse(c.count));
+        top_keywords.truncate(top);
+
+        CatalogStats {
+            products: products.len(),
+            in_stock: status(Availability::is_in_stock),
+            out_of_stock: status(|a| *a == Availability::OutOfStock),
+            pre_order: status(|a| matches!(a, Availability::PreOrder { .. })),
+            discontinued: status(|a| *a == Availability::Discontinued),
+            prices: prices
+                .into_iter()
+                .map(|(currency, prices)| (currency, PriceStats::from_prices(prices)))
+                .collect(),
+            rating_distribution,
+            user_rating_distribution,
+            user_ratings: list_stats(
+                products.iter().map(|p| p.user_ratings.len()).sum(),
+                products.len(),
+            ),
+            reviews: list_stats(
+                products.iter().map(|p| p.reviews.len()).sum(),
+                products.len(),
+            ),
+            top_ingredients,
+            skin_types: count_values(products.iter().map(|p| p.skin_type.as_str())),
+            review_sentiment,
+            top_keywords,
+        }
+    }
+}
+
+fn write_counts(f: &mut fmt::Formatter<'_>, title: &str, counts: &[Count]) -> fmt::Result {
+    writeln!(f, "\n{}", title)?;
+    let width = counts
+        .iter()
+        .map(|c| c.name.chars().count())
+        .max()
+        .unwrap_or(0);
+    for count in counts {
+        writeln!(f, "  {:<width$}  {:>5}", count.name, count.count)?;
+    }
+    Ok(())
+}
+
+impl fmt::Display for CatalogStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Products      {:>8}", self.products)?;
+        writeln!(f, "In stock      {:>8}", self.in_stock)?;
+        writeln!(f, "Out of stock  {:>8}", self.out_of_stock)?;
+        writeln!(f, "Pre-order     {:>8}", self.pre_order)?;
+        writeln!(f, "Discontinued  {:>8}", self.discontinued)?;
+        writeln!(
+            f,
+            "User ratings  {:>8}  ({:.2} per product)",
+            self.user_ratings.total, self.user_ratings.average_per_product
+        )?;
+        writeln!(
+            f,
+            "Reviews       {:>8}  ({:.2} per product)",
+            self.reviews.total, self.reviews.average_per_product
+        )?;
+
+        writeln!(f, "\nPrices")?;
+        writeln!(
+            f,
+            "  {:<8}  {:>5}  {:>10}  {:>10}  {:>10}  {:>10}",
+            "Currency", "Count", "Min", "Max", "Mean", "Median"
+        )?;
+        for (currency, prices) in &self.prices {
+            let decimals = Currency::parse(currency).map_or(2, |c| c.decimals()) as usize;
+            writeln!(
+                f,
+                "  {:<8}  {:>5}  {:>10.decimals$}  {:>10.decimals$}  {:>10.decimals$}  {:>10.decimals$}",
+                currency, prices.count, prices.min, prices.max, prices.mean, prices.median
+            )?;
+        }
+
+        write_counts(f, "Rating distribution", &self.rating_distribution)?;
+        write_counts(
+            f,
+            "User rating distribution",
+            &self.user_rating_distribution,
+        )?;
+        write_counts(f, "Top ingredients", &self.top_ingredients)?;
+        write_counts(f, "Skin types", &self.skin_types)?;
+
+        writeln!(f, "\nReview sentiment")?;
+        for product in &self.review_sentiment {
+            let sentiment = &product.sentiment;
+            write!(
+                f,
+                "  {:+.2}  {}  (rating {:.1}, reviews suggest {:.1})",
+                sentiment.score,
+                product.product_name,
+                product.rating,
+                sentiment.expected_rating()
+            )?;
+            if sentiment.contradicts_rating {
+                write!(f, "  contradicts rating")?;
+            }
+            writeln!(f)?;
+        }
+        write_counts(f, "Review keywords", &self.top_keywords)
+    }
+}
@@ -0,0 +1,103 @@
+use cosmetics_parser::allergens::*;
+use cosmetics_parser::config;
+use cosmetics_parser::query::*;
+use cosmetics_parser::*;
+use std::fs;
+
+#[test]
+fn test_detect() -> anyhow::Result<()> {
+    let list = AllergenList::builtin();
+    let findings = list.detect(
+        "Aqua, Prunus Amygdalus Dulcis (Sweet Almond) Oil, alpha-isomethyl ionone, Linalool, \
+         Almondine, Sweet Almond Oil",
+    );
+    let found: Vec<(&str, &str, &str)> = findings
+        .iter()
+        .map(|f| {
+            (
+                f.substance.as_str(),
+                f.category.as_str(),
+                f.ingredient.as_str(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        found,
+        vec![
+            (
+                "Almond Oil",
+                "nut oil",
+                "Prunus Amygdalus Dulcis (Sweet Almond) Oil"
+            ),
+            (
+                "Alpha-Isomethyl Ionone",
+                "fragrance allergen",
+                "alpha-isomethyl ionone"
+            ),
+            ("Linalool", "fragrance allergen", "Linalool"),
+        ]
+    );
+
+    assert!(list.detect("Water, Glycerin, Hyaluronic Acid").is_empty());
+    assert_eq!(
+        list.substances
+            .iter()
+            .filter(|s| s.category == "fragrance allergen")
+            .count(),
+        26
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_custom_list() -> anyhow::Result<()> {
+    let list = config::from_toml::<AllergenList>(
+        "[[substances]]\nname = \"Jojoba Oil\"\naliases = [\"Simmondsia Chinensis Seed Oil\"]\n\
+         category = \"client list\"\n",
+    )?;
+    let findings = list.detect("Simmondsia Chinensis Seed Oil, Coconut Oil");
+    assert_eq!(findings.len(), 1);
+    assert_eq!(findings[0].substance, "Jojoba Oil");
+
+    let list = config::from_json::<AllergenList>(r#"{ "substances": [{ "name": "Retinol" }] }"#)?;
+    assert_eq!(list.detect("Retinol, Squalane")[0].category, "");
+
+    let error = config::from_toml::<AllergenList>("[[substances]]\naliases = []\n");
+    assert!(error.is_err(), "Expected error but got {:?}", error);
+
+    Ok(())
+}
+
+#[test]
+fn test_report() -> anyhow::Result<()> {
+    let file_content = fs::read_to_string("src/input.txt")?;
+    let (mut products, errors) = parse_catalog(&file_content);
+    assert!(errors.is_empty(), "unexpected errors {:?}", errors);
+
+    let list = AllergenList::builtin();
+    let report = list.report(&products);
+    assert_eq!(report.checked, products.len());
+    let flagged: Vec<Option<u64>> = report.products.iter().map(|p| p.product_id).collect();
+    assert_eq!(flagged, vec![Some(4), Some(6)]);
+    assert!(report
+        .to_string()
+        .ends_with("2 of 7 products contain flagged substances\n"));
+
+    assert!(products[3].allergens.is_empty());
+    list.annotate(&mut products);
+    assert_eq!(
+        products[3].allergens,
+        vec!["Shea Butter", "Coconut Oil", "Almond Oil"]
+    );
+    let json = serde_json::to_value(&products[3])?;
+    assert_eq!(json["allergens"][1], "Coconut Oil");
+    assert!(serde_json::to_value(&products[0])?
+        .get("allergens")
+        .is_none());
+
+    let query = Query::parse("allergens contains \"Coconut Oil\"")?;
+    assert_eq!(query.filter(&products).len(), 2);
+
+    Ok(())
+}
@@ -0,0 +1,101 @@
+mod common;
+
+use chrono::NaiveDate;
+use common::first_product_with;
+use cosmetics_parser::availability::*;
+use cosmetics_parser::*;
+
+/// The first product of the sample catalog with its availability line replaced
+fn with_availability(status: &str) -> anyhow::Result<String> {
+    first_product_with(
+        "*Availability*: false",
+        &format!("*Availability*: {}", status),
+    )
+}
+#[test]
+fn test_stock_statuses() -> anyhow::Result<()> {
+    let until = NaiveDate::from_ymd_opt(2026, 12, 1);
+    let cases = [
+        ("true", Availability::InStock { quantity: None }),
+        ("false", Availability::OutOfStock),
+        ("так", Availability::InStock { quantity: None }),
+        ("in stock", Availability::InStock { quantity: None }),
+        (
+            "in stock (12)",
+            Availability::InStock { quantity: Some(12) },
+        ),
+        ("In Stock(3)", Availability::InStock { quantity: Some(3) }),
+        (
+            "в наявності (5)",
+            Availability::InStock { quantity: Some(5) },
+        ),
+        ("out of stock", Availability::OutOfStock),
+        ("немає в наявності", Availability::OutOfStock),
+        ("pre-order", Availability::PreOrder { until: None }),
+        (
+            "pre-order until 2026-12-01",
+            Availability::PreOrder { until },
+        ),
+        (
+            "передзамовлення до 2026-12-01",
+            Availability::PreOrder { until },
+        ),
+        ("discontinued", Availability::Discontinued),
+    ];
+
+    for (status, availability) in cases {
+        let (products, errors) = parse_catalog(&with_availability(status)?);
+        assert!(errors.is_empty(), "{}: {:?}", status, errors);
+        assert_eq!(products[0].availability, availability, "{}", status);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_invalid_statuses() -> anyhow::Result<()> {
+    let (_, errors) = parse_catalog(&with_availability("pre-order until 2026-02-30")?);
+    assert_eq!(
+        errors[0].to_string(),
+        "line 10: invalid pre-order date `2026-02-30`"
+    );
+
+    let (_, errors) = parse_catalog(&with_availability("in stock (99999999999999999999)")?);
+    assert!(errors[0].to_string().contains("too large"), "{:?}", errors);
+
+    let (_, errors) = parse_catalog(&with_availability("maybe")?);
+    assert_eq!(errors.len(), 1);
+
+    Ok(())
+}
+
+#[test]
+fn test_json() -> anyhow::Result<()> {
+    let (products, _) = parse_catalog(&with_availability("in stock (12)")?);
+    let json = serde_json::to_value(&products[0])?;
+    assert_eq!(
+        json["availability"],
+        serde_json::json!({ "status": "in_stock", "quantity": 12 })
+    );
+    let product: Product = serde_json::from_value(json)?;
+    assert_eq!(product.availability.quantity(), Some(12));
+
+    // Output written when availability was a plain flag still loads
+    let mut json = serde_json::to_value(&products[0])?;
+    json["availability"] = serde_json::json!(true);
+    let product: Product = serde_json::from_value(json)?;
+    assert_eq!(
+        product.availability,
+        Availability::InStock { quantity: None }
+    );
+
+    assert_eq!(
+        Availability::PreOrder {
+            until: NaiveDate::from_ymd_opt(2026, 12, 1)
+        }
+        .to_string(),
+        "pre-order until 2026-12-01"
+    );
+
+    Ok(())
+}
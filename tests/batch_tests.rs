@@ -0,0 +1,129 @@
+use anyhow::anyhow;
+use cosmetics_parser::batch::*;
+use cosmetics_parser::*;
+use std::fs;
+use std::path::PathBuf;
+
+const BROKEN_PRODUCT: &str = "*Product 8*: Toner \"Fresh\"
+*Skin Type*: Oily Skin
+*Rating*: 4.1
+";
+
+fn temp_dir(name: &str) -> anyhow::Result<PathBuf> {
+    let dir =
+        std::env::temp_dir().join(format!("cosmetics_parser_{}_{}", name, std::process::id()));
+    if dir.exists() {
+        fs::remove_dir_all(&dir)?;
+    }
+    fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+#[test]
+fn test_product_blocks() -> anyhow::Result<()> {
+    let file_content = fs::read_to_string("src/input.txt")?;
+
+    let blocks = product_blocks(&file_content);
+    assert_eq!(blocks.len(), 7);
+    assert_eq!(blocks[0].line, 1);
+    assert_eq!(blocks[1].line, 12);
+    assert_eq!(blocks[1].offset, 417);
+    assert!(blocks[6].text.starts_with("*Product 7*"));
+
+    Ok(())
+}
+
+#[test]
+fn test_parse_catalog_collects_errors() -> anyhow::Result<()> {
+    let file_content = fs::read_to_string("src/input.txt")?;
+    let input = format!("{}{}", file_content, BROKEN_PRODUCT);
+
+    let (products, errors) = parse_catalog(&input);
+    assert_eq!(products.len(), 7);
+    assert_eq!(errors.len(), 1);
+
+    let error = errors.first().ok_or_else(|| anyhow!("no error"))?;
+    assert!(error.to_string().starts_with("line 86:"), "got {}", error);
+
+    Ok(())
+}
+
+#[test]
+fn test_collect_inputs() -> anyhow::Result<()> {
+    let dir = temp_dir("collect")?;
+    fs::create_dir_all(dir.join("nested"))?;
+    fs::copy("src/input.txt", dir.join("a.md"))?;
+    fs::write(dir.join("nested/b.md"), BROKEN_PRODUCT)?;
+    fs::write(dir.join("notes.json"), "[]")?;
+
+    let inputs = vec![dir.display().to_string()];
+    let files = collect_inputs(&inputs)?;
+    assert_eq!(files, vec![dir.join("a.md"), dir.join("nested/b.md")]);
+
+    let inputs = vec![
+        dir.join("*.md").display().to_string(),
+        dir.join("a.md").display().to_string(),
+    ];
+    let files = collect_inputs(&inputs)?;
+    assert_eq!(files, vec![dir.join("a.md")]);
+
+    let inputs = vec![
+        dir.join("nested/b.md").display().to_string(),
+        dir.display().to_string(),
+    ];
+    let files = collect_inputs(&inputs)?;
+    assert_eq!(files, vec![dir.join("a.md"), dir.join("nested/b.md")]);
+
+    let inputs = vec![dir.join("*.toml").display().to_string()];
+    assert!(collect_inputs(&inputs).is_err());
+
+    fs::remove_dir_all(&dir)?;
+    Ok(())
+}
+
+#[test]
+fn test_merge_and_split() -> anyhow::Result<()> {
+    let dir = temp_dir("merge")?;
+    fs::copy("src/input.txt", dir.join("a.md"))?;
+    fs::write(dir.join("b.md"), BROKEN_PRODUCT)?;
+
+    let files = collect_inputs(&[dir.display().to_string()])?;
+    let reports = parse_paths(&files);
+    assert_eq!(reports[0].products.len(), 7);
+    assert_eq!(reports[1].errors.len(), 1);
+
+    let written = write_separate(&reports, &dir.join("out"))?;
+    assert_eq!(
+        written,
+        vec![dir.join("out/a.json"), dir.join("out/b.json")]
+    );
+
+    let merged = merge_reports(reports);
+    assert_eq!(merged.len(), 7);
+    let json = serde_json::to_value(&merged[0])?;
+    assert_eq!(json["source"], dir.join("a.md").display().to_string());
+    assert_eq!(json["product_name"], "Face Cream \"Moisturizing\"");
+
+    fs::remove_dir_all(&dir)?;
+    Ok(())
+}
+
+#[test]
+fn test_unreadable_file_does_not_stop_batch() -> anyhow::Result<()> {
+    let dir = temp_dir("unreadable")?;
+    fs::copy("src/input.txt", dir.join("a.md"))?;
+    fs::write(dir.join("c.md"), BROKEN_PRODUCT)?;
+
+    let files = vec![dir.join("a.md"), dir.join("b.md"), dir.join("c.md")];
+    let reports = parse_paths(&files);
+    assert_eq!(reports.len(), 3);
+    assert_eq!(reports[0].products.len(), 7);
+    assert_eq!(reports[1].path, dir.join("b.md"));
+    assert!(reports[1].products.is_empty());
+    assert_eq!(reports[1].errors.len(), 1);
+    assert!(matches!(reports[1].errors[0], ParseError::Io(_)));
+    assert_eq!(reports[2].errors.len(), 1);
+
+    fs::remove_dir_all(&dir)?;
+    Ok(())
+}
@@ -0,0 +1,23 @@
+// Each test crate uses only some of these helpers
+#![allow(dead_code)]
+
+use cosmetics_parser::*;
+use std::fs;
+
+/// The products of the sample catalog
+pub fn catalog() -> anyhow::Result<Vec<Product>> {
+    let file_content = fs::read_to_string("src/input.txt")?;
+    Ok(parse_catalog(&file_content).0)
+}
+
+/// The text of the first product of the sample catalog with `line` replaced by `replacement`
+pub fn first_product_with(line: &str, replacement: &str) -> anyhow::Result<String> {
+    let file_content = fs::read_to_string("src/input.txt")?;
+    let block = product_blocks(&file_content)[0].text;
+    Ok(block.replace(line, replacement))
+}
+
+/// The first product of the sample catalog with its price line replaced
+pub fn with_price(price: &str) -> anyhow::Result<String> {
+    first_product_with("*Price*: 299.99 UAH", &format!("*Price*: {}", price))
+}
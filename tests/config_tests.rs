@@ -0,0 +1,42 @@
+use cosmetics_parser::allergens::AllergenList;
+use cosmetics_parser::config::*;
+use cosmetics_parser::sentiment::Lexicon;
+use std::fs;
+use std::path::Path;
+
+#[test]
+fn test_load_toml_or_json() -> anyhow::Result<()> {
+    let dir = std::env::temp_dir().join(format!("cosmetics_parser_config_{}", std::process::id()));
+    fs::create_dir_all(&dir)?;
+    fs::write(
+        dir.join("list.toml"),
+        "[[substances]]\nname = \"Jojoba Oil\"\n",
+    )?;
+    fs::write(
+        dir.join("list.json"),
+        r#"{ "substances": [{ "name": "Retinol" }] }"#,
+    )?;
+
+    let list: AllergenList = load_toml_or_json(&dir.join("list.toml"))?;
+    assert_eq!(list.substances[0].name, "Jojoba Oil");
+    let list: AllergenList = load_toml_or_json(&dir.join("list.json"))?;
+    assert_eq!(list.substances[0].name, "Retinol");
+
+    fs::remove_dir_all(&dir)?;
+    Ok(())
+}
+
+#[test]
+fn test_errors() {
+    let error = load_toml_or_json::<Lexicon>(Path::new("no/such/lexicon.toml"));
+    assert!(
+        matches!(&error, Err(ConfigError::Io { path, .. }) if path == Path::new("no/such/lexicon.toml")),
+        "got {:?}",
+        error
+    );
+
+    let error = from_toml::<AllergenList>("[[substances]]\naliases = []\n");
+    assert!(matches!(error, Err(ConfigError::Toml(_))));
+    let error = from_json::<Lexicon>("{\"terms\": 1}");
+    assert!(error.is_err_and(|e| e.to_string().starts_with("invalid JSON: ")));
+}
@@ -0,0 +1,91 @@
+mod common;
+
+use common::catalog;
+use cosmetics_parser::config;
+use cosmetics_parser::conflicts::*;
+
+#[test]
+fn test_check_product() -> anyhow::Result<()> {
+    let mut products = catalog()?;
+    let rules = ConflictRules::builtin();
+    assert!(rules.check_catalog(&products).is_empty());
+
+    products[2].ingredients = String::from("Retinol, Niacinamide, Salicylic Acid (BHA)");
+    let conflicts = rules.check_catalog(&products);
+    assert_eq!(conflicts.len(), 1);
+    assert_eq!(conflicts[0].rule, "retinoid + AHA/BHA");
+    assert_eq!(conflicts[0].first.ingredient, "Retinol");
+    assert_eq!(conflicts[0].second.ingredient, "Salicylic Acid (BHA)");
+    assert_eq!(conflicts[0].first.product_id, Some(3));
+    assert!(conflicts[0]
+        .to_string()
+        .starts_with("retinoid + AHA/BHA: Retinol in Night Serum \"Revitalizing\" + Salicylic Acid (BHA) in Night Serum \"Revitalizing\". Both exfoliate"));
+
+    products[2].ingredients = String::from("Retinol, Vitamin C, BHA");
+    let rules: Vec<String> = rules
+        .check_product(&products[2])
+        .into_iter()
+        .map(|c| c.rule)
+        .collect();
+    assert_eq!(rules, vec!["retinoid + vitamin C"]);
+
+    Ok(())
+}
+
+#[test]
+fn test_check_routine() -> anyhow::Result<()> {
+    let products = catalog()?;
+    let rules = ConflictRules::builtin();
+
+    let conflicts = rules.check_ids(&products, &[3, 4, 3])?;
+    let found: Vec<(&str, &str, &str)> = conflicts
+        .iter()
+        .map(|c| {
+            (
+                c.rule.as_str(),
+                c.first.ingredient.as_str(),
+                c.second.ingredient.as_str(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        found,
+        vec![
+            ("vitamin C + niacinamide", "Vitamin C", "Niacinamide"),
+            ("retinoid + vitamin C", "Retinol", "Vitamin C"),
+        ]
+    );
+
+    assert!(rules.check_ids(&products, &[1, 2])?.is_empty());
+    let error = rules.check_ids(&products, &[3, 42]);
+    assert_eq!(
+        error.err().map(|e| e.to_string()),
+        Some(String::from("no product with id 42 in the catalog"))
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_rule_file() -> anyhow::Result<()> {
+    let products = catalog()?;
+    let rules = config::from_toml::<ConflictRules>(
+        "[[rules]]\nname = \"clay + oil\"\nfirst = [\"Clay\"]\nsecond = [\"Tea Tree Oil\"]\n",
+    )?;
+    let conflicts = rules.check_catalog(&products);
+    assert_eq!(conflicts.len(), 1);
+    assert_eq!(conflicts[0].first.ingredient, "Bentonite Clay");
+    assert_eq!(conflicts[0].reason, "");
+    assert_eq!(
+        conflicts[0].to_string(),
+        "clay + oil: Bentonite Clay in Face Mask \"Deep Cleansing Clay\" + Tea Tree Oil in Face Mask \"Deep Cleansing Clay\""
+    );
+
+    let rules = config::from_json::<ConflictRules>(
+        r#"{ "rules": [{ "name": "x", "first": ["Retinol"], "second": ["Vitamin C"] }] }"#,
+    )?;
+    assert_eq!(rules.check_ids(&products, &[3, 4])?.len(), 1);
+    assert!(config::from_toml::<ConflictRules>("[[rules]]\nname = \"x\"\n").is_err());
+
+    Ok(())
+}
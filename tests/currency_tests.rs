@@ -0,0 +1,106 @@
+mod common;
+
+use common::with_price;
+use cosmetics_parser::config;
+use cosmetics_parser::currency::*;
+use cosmetics_parser::exchange::ExchangeRates;
+use cosmetics_parser::*;
+
+#[test]
+fn test_codes_and_symbols() -> anyhow::Result<()> {
+    let cases = [
+        ("299.99 UAH", Currency::Uah),
+        ("299.99 ₴", Currency::Uah),
+        ("299.99 грн", Currency::Uah),
+        ("₴299.99", Currency::Uah),
+        ("€ 12.50", Currency::Eur),
+        ("$12.50", Currency::Usd),
+        ("12.50 zł", Currency::Pln),
+        ("£12", Currency::Gbp),
+        ("49.90 GBP", Currency::Gbp),
+        ("1500 JPY", Currency::Jpy),
+        ("150000 IDR", Currency::Other(String::from("IDR"))),
+    ];
+
+    for (price, currency) in cases {
+        let (products, errors) = parse_catalog(&with_price(price)?);
+        assert!(errors.is_empty(), "{}: {:?}", price, errors);
+        assert_eq!(products[0].currency.as_ref(), Some(&currency), "{}", price);
+    }
+
+    let (products, _) = parse_catalog(&with_price("$12.50")?);
+    assert_eq!(products[0].price, 12.5);
+
+    let (products, _) = parse_catalog(&with_price("12.50")?);
+    assert_eq!(products[0].currency, None);
+
+    Ok(())
+}
+
+#[test]
+fn test_unknown_currency() -> anyhow::Result<()> {
+    let (products, errors) = parse_catalog(&with_price("12.50 XYZ")?);
+
+    assert!(products.is_empty());
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].to_string(), "line 5: unknown currency `XYZ`");
+
+    Ok(())
+}
+
+#[test]
+fn test_custom_registry() -> anyhow::Result<()> {
+    let registry = config::from_toml::<CurrencyRegistry>(
+        r#"
+        [[currencies]]
+        code = "BTC"
+        symbols = ["₿"]
+        decimals = 8
+        "#,
+    )?;
+    let options = ParseOptions {
+        currencies: registry.clone(),
+        ..ParseOptions::default()
+    };
+
+    let (products, errors) = parse_catalog_with(&with_price("₿0.0042")?, &options);
+    assert!(errors.is_empty(), "{:?}", errors);
+    let btc = Currency::Other(String::from("BTC"));
+    assert_eq!(products[0].currency.as_ref(), Some(&btc));
+    assert_eq!(registry.decimals(&btc), 8);
+    assert_eq!(registry.parse("₴")?, Currency::Uah);
+
+    let rates = ExchangeRates::from_toml(
+        "[[rates]]\nfrom = \"UAH\"\nto = \"BTC\"\nrate = 0.0000001234\neffective = \"2024-11-01\"\n",
+    )?
+    .with_currencies(registry.clone());
+    let date =
+        chrono::NaiveDate::from_ymd_opt(2025, 1, 1).ok_or_else(|| anyhow::anyhow!("bad date"))?;
+    assert_eq!(rates.convert(1000.0, "UAH", "BTC", date)?, 0.0001234);
+
+    // The registry only applies where it is passed
+    let (products, errors) = parse_catalog(&with_price("₿0.0042")?);
+    assert!(products.is_empty());
+    assert_eq!(errors[0].to_string(), "line 5: unknown currency `₿`");
+    assert_eq!(btc.decimals(), 2);
+
+    Ok(())
+}
+
+#[test]
+fn test_precision_and_serialization() -> anyhow::Result<()> {
+    assert_eq!(Currency::Uah.decimals(), 2);
+    assert_eq!(Currency::Jpy.decimals(), 0);
+    assert_eq!("KWD".parse::<Currency>()?.decimals(), 3);
+    assert_eq!(Currency::Jpy.format(1500.0), "1500 JPY");
+    assert_eq!(Currency::Eur.format(12.5), "12.50 EUR");
+
+    assert_eq!(serde_json::to_string(&Currency::Uah)?, "\"UAH\"");
+    assert_eq!(serde_json::from_str::<Currency>("\"€\"")?, Currency::Eur);
+    assert_eq!(
+        serde_json::from_str::<Currency>("\"BTC\"")?,
+        Currency::Other(String::from("BTC"))
+    );
+
+    Ok(())
+}
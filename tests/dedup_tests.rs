@@ -0,0 +1,93 @@
+use cosmetics_parser::dedup::*;
+use cosmetics_parser::merge::ConflictPolicy;
+use cosmetics_parser::*;
+use std::fs;
+
+fn catalog_with_duplicate() -> anyhow::Result<Vec<Product>> {
+    let file_content = fs::read_to_string("src/input.txt")?;
+    let (mut products, _) = parse_catalog(&file_content);
+
+    let mut duplicate = products[0].clone();
+    duplicate.product_id = Some(100);
+    duplicate.product_name = String::from("Face cream Moisturising");
+    duplicate.price = 289.99;
+    duplicate.reviews = vec![String::from("1. \"Lovely\"")];
+    products.push(duplicate);
+    Ok(products)
+}
+
+#[test]
+fn test_similarity_parts() -> anyhow::Result<()> {
+    assert_eq!(
+        name_similarity("Face Cream \"Moisturizing\"", "face cream moisturizing"),
+        1.0
+    );
+    let fuzzy = name_similarity("Face Cream \"Moisturizing\"", "Face cream Moisturising");
+    assert!(fuzzy > 0.85 && fuzzy < 1.0, "{}", fuzzy);
+    assert!(name_similarity("Face Cream", "Lip Balm") < 0.3);
+
+    assert_eq!(
+        ingredient_overlap("Water, Aloe Vera", "aloe-vera, water"),
+        1.0
+    );
+    assert_eq!(
+        ingredient_overlap("Water, Glycerin", "Water, Retinol"),
+        1.0 / 3.0
+    );
+    assert_eq!(ingredient_overlap("", ""), 1.0);
+
+    assert_eq!(price_proximity(100.0, 50.0), 0.5);
+    assert_eq!(price_proximity(0.0, 0.0), 1.0);
+    Ok(())
+}
+
+#[test]
+fn test_find_duplicates() -> anyhow::Result<()> {
+    let products = catalog_with_duplicate()?;
+    let report = find_duplicates(&products, &DedupOptions::default());
+
+    assert_eq!(report.checked, products.len());
+    assert_eq!(report.clusters.len(), 1);
+    let cluster = &report.clusters[0];
+    let ids: Vec<Option<u64>> = cluster.members.iter().map(|m| m.product_id).collect();
+    assert_eq!(ids, vec![Some(1), Some(100)]);
+
+    let similarity = cluster.pairs[0].similarity;
+    assert_eq!(similarity.ingredients, 0.3);
+    assert_eq!(
+        similarity.score,
+        ((similarity.name + similarity.ingredients + similarity.price) * 1000.0).round() / 1000.0
+    );
+    assert!(report.to_string().ends_with(&format!(
+        "1 cluster(s) of likely duplicates among {} products\n",
+        products.len()
+    )));
+
+    let strict = DedupOptions {
+        threshold: 0.99,
+        ..DedupOptions::default()
+    };
+    assert!(find_duplicates(&products, &strict).clusters.is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_merge_duplicates() -> anyhow::Result<()> {
+    let products = catalog_with_duplicate()?;
+    let report = find_duplicates(&products, &DedupOptions::default());
+
+    let merged = report.merge(&products, ConflictPolicy::LowestPrice)?;
+    assert_eq!(merged.products.len(), products.len() - 1);
+    assert_eq!(merged.products[0].product_name, "Face cream Moisturising");
+    assert_eq!(merged.products[0].price, 289.99);
+    assert_eq!(merged.products[1].product_id, products[1].product_id);
+
+    let union = report.merge(&products, ConflictPolicy::Union)?;
+    assert_eq!(
+        union.products[0].reviews.len(),
+        products[0].reviews.len() + 1
+    );
+
+    assert!(report.merge(&products, ConflictPolicy::Fail).is_err());
+    Ok(())
+}
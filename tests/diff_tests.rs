@@ -0,0 +1,233 @@
+use cosmetics_parser::availability::Availability;
+use cosmetics_parser::diff::*;
+use cosmetics_parser::*;
+use serde_json::{json, Value};
+use std::fs;
+
+fn catalogs() -> anyhow::Result<(Vec<Product>, Vec<Product>)> {
+    let file_content = fs::read_to_string("src/input.txt")?;
+    let (old, _) = parse_catalog(&file_content);
+
+    let mut new = old.clone();
+    new[0].price = 319.99;
+    new[0].availability = Availability::InStock { quantity: None };
+    new[0].ingredients = String::from("Water, Glycerin, Hyaluronic Acid, Niacinamide");
+    new[2].reviews.push(String::from("3. \"Love it\""));
+    new.remove(1);
+    let mut added = new[0].clone();
+    added.product_id = Some(8);
+    added.product_name = String::from("Toner \"Fresh\"");
+    new.push(added);
+
+    Ok((old, new))
+}
+
+/// Applies the add, replace and remove operations of an RFC 6902 patch to `document`
+fn apply_patch(document: &mut Value, patch: &Value) -> anyhow::Result<()> {
+    let ops = patch
+        .as_array()
+        .ok_or_else(|| anyhow::anyhow!("not an array"))?;
+    for op in ops {
+        let path = op["path"]
+            .as_str()
+            .ok_or_else(|| anyhow::anyhow!("no path in {}", op))?;
+        let (parent, key) = path
+            .rsplit_once('/')
+            .ok_or_else(|| anyhow::anyhow!("bad path {}", path))?;
+        let target = document
+            .pointer_mut(parent)
+            .ok_or_else(|| anyhow::anyhow!("no parent for {}", path))?;
+        match (op["op"].as_str(), target) {
+            (Some("add"), Value::Array(items)) if key == "-" => items.push(op["value"].clone()),
+            (Some("add"), Value::Object(fields)) => {
+                fields.insert(key.to_string(), op["value"].clone());
+            }
+            (Some("replace"), Value::Object(fields)) if fields.contains_key(key) => {
+                fields.insert(key.to_string(), op["value"].clone());
+            }
+            (Some("remove"), Value::Object(fields)) if fields.contains_key(key) => {
+                fields.remove(key);
+            }
+            (Some("remove"), Value::Array(items)) => {
+                items.remove(key.parse::<usize>()?);
+            }
+            _ => anyhow::bail!("cannot apply {}", op),
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_catalog_diff() -> anyhow::Result<()> {
+    let (old, new) = catalogs()?;
+
+    let diff = CatalogDiff::new(&old, &new);
+
+    assert_eq!(diff.added.len(), 1);
+    assert_eq!(diff.added[0].product_id, Some(8));
+    assert_eq!(diff.removed.len(), 1);
+    assert_eq!(diff.removed[0].product_id, Some(2));
+
+    assert_eq!(diff.changed.len(), 2);
+    assert_eq!(
+        diff.changed[0].changes,
+        vec![
+            Change::Price {
+                old: 299.99,
+                new: 319.99
+            },
+            Change::Availability {
+                old: Availability::OutOfStock,
+                new: Availability::InStock { quantity: None }
+            },
+            Change::IngredientsAdded {
+                ingredients: vec![String::from("Niacinamide")]
+            },
+            Change::IngredientsRemoved {
+                ingredients: vec![String::from("Jojoba Oil")]
+            },
+        ]
+    );
+    assert_eq!(
+        diff.changed[1].changes,
+        vec![Change::ReviewsAdded {
+            reviews: vec![String::from("3. \"Love it\"")]
+        }]
+    );
+
+    let report = diff.to_string();
+    assert!(report.contains("+ #8 Toner \"Fresh\""), "got {}", report);
+    assert!(report.contains("price increased from 299.99 to 319.99 (+6.7%)"));
+
+    assert!(CatalogDiff::new(&old, &old).is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn test_diff_falls_back_to_name() -> anyhow::Result<()> {
+    let (old, _) = catalogs()?;
+    let mut new = old.clone();
+    new.iter_mut().for_each(|p| p.product_id = None);
+    new[3].rating = 4.9;
+
+    let diff = CatalogDiff::new(&old, &new);
+
+    assert!(diff.added.is_empty() && diff.removed.is_empty());
+    assert_eq!(diff.changed.len(), old.len());
+    assert_eq!(diff.changed[3].product_name, old[3].product_name);
+    let fields: Vec<&str> = diff.changed[3].changes.iter().map(Change::field).collect();
+    assert_eq!(fields, vec!["product_id", "rating"]);
+    assert_eq!(
+        diff.changed[0].changes,
+        vec![Change::Field {
+            field: String::from("product_id"),
+            old: json!(1),
+            new: Value::Null
+        }]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_json_patch() -> anyhow::Result<()> {
+    let (old, new) = catalogs()?;
+    let diff = CatalogDiff::new(&old, &new);
+
+    let patch = diff.to_json_patch(&old, &new);
+    let ops = patch
+        .as_array()
+        .ok_or_else(|| anyhow::anyhow!("not an array"))?;
+
+    assert!(ops.contains(&json!({ "op": "replace", "path": "/0/price", "value": 319.99 })));
+    assert!(ops
+        .iter()
+        .any(|op| op["op"] == "remove" && op["path"] == "/1"));
+    let last = ops.last().ok_or_else(|| anyhow::anyhow!("empty patch"))?;
+    assert_eq!(last["op"], "add");
+    assert_eq!(last["path"], "/-");
+    assert_eq!(last["value"]["product_id"], 8);
+
+    Ok(())
+}
+
+#[test]
+fn test_json_patch_applies() -> anyhow::Result<()> {
+    let (mut old, mut new) = catalogs()?;
+    old[0]
+        .extra
+        .insert(String::from("Brand"), String::from("Acme"));
+    new[0].original_price = Some(399.99);
+
+    let patch = CatalogDiff::new(&old, &new).to_json_patch(&old, &new);
+    let ops = patch
+        .as_array()
+        .ok_or_else(|| anyhow::anyhow!("not an array"))?;
+    assert!(ops.contains(&json!({ "op": "remove", "path": "/0/extra" })));
+    assert!(ops.contains(&json!({ "op": "add", "path": "/0/original_price", "value": 399.99 })));
+
+    let mut document = serde_json::to_value(&old)?;
+    apply_patch(&mut document, &patch)?;
+    assert_eq!(document, serde_json::to_value(&new)?);
+
+    Ok(())
+}
+
+#[test]
+fn test_json_patch_updates_review_stars() -> anyhow::Result<()> {
+    let file_content = fs::read_to_string("src/input.txt")?;
+    let first = product_blocks(&file_content)[0].text;
+    let starred = first.replacen("1. \"", "1. (5/5) \"", 1);
+    let (old, errors) = parse_catalog(&starred);
+    assert!(errors.is_empty(), "{:?}", errors);
+    let (new, errors) = parse_catalog(&starred.replace("(5/5)", "(2/5)"));
+    assert!(errors.is_empty(), "{:?}", errors);
+    assert_eq!(new[0].review_stars[0].stars, 2.0);
+
+    let diff = CatalogDiff::new(&old, &new);
+    let fields: Vec<&str> = diff.changed[0].changes.iter().map(Change::field).collect();
+    assert!(fields.contains(&"review_stars"), "got {:?}", fields);
+
+    let mut document = serde_json::to_value(&old)?;
+    apply_patch(&mut document, &diff.to_json_patch(&old, &new))?;
+    assert_eq!(document, serde_json::to_value(&new)?);
+
+    Ok(())
+}
+
+#[test]
+fn test_diff_every_field() -> anyhow::Result<()> {
+    let (old, _) = catalogs()?;
+    let mut new = old.clone();
+    new[0].product_id = Some(42);
+    new[0].allergens = vec![String::from("Linalool")];
+    new[1].reviews.reverse();
+    new[2].ingredients = old[2]
+        .ingredients
+        .rsplit(", ")
+        .collect::<Vec<_>>()
+        .join(", ");
+    new[3].source_map = None;
+
+    let diff = CatalogDiff::new(&old, &new);
+    let fields: Vec<Vec<&str>> = diff
+        .changed
+        .iter()
+        .map(|d| d.changes.iter().map(Change::field).collect())
+        .collect();
+    assert_eq!(
+        fields,
+        vec![
+            vec!["product_id", "allergens"],
+            vec!["reviews"],
+            vec!["ingredients"]
+        ]
+    );
+
+    let mut document = serde_json::to_value(&old)?;
+    apply_patch(&mut document, &diff.to_json_patch(&old, &new))?;
+    assert_eq!(document, serde_json::to_value(&new)?);
+
+    Ok(())
+}
@@ -0,0 +1,126 @@
+use chrono::NaiveDate;
+use cosmetics_parser::config::ConfigError;
+use cosmetics_parser::currency::Currency;
+use cosmetics_parser::exchange::*;
+use cosmetics_parser::*;
+use std::fs;
+
+const RATES: &str = r#"
+[[rates]]
+from = "EUR"
+to = "UAH"
+rate = 44.2
+effective = "2024-11-01"
+
+[[rates]]
+from = "EUR"
+to = "UAH"
+rate = 45.0
+effective = "2025-01-01"
+
+[[rates]]
+from = "USD"
+to = "UAH"
+rate = 41.0
+effective = "2024-11-01"
+"#;
+
+fn date(s: &str) -> anyhow::Result<NaiveDate> {
+    Ok(s.parse()?)
+}
+
+#[test]
+fn test_effective_dates() -> anyhow::Result<()> {
+    let rates = ExchangeRates::from_toml(RATES)?;
+
+    assert_eq!(rates.rate("EUR", "UAH", date("2024-12-31")?)?, 44.2);
+    assert_eq!(rates.rate("EUR", "UAH", date("2025-01-01")?)?, 45.0);
+    assert_eq!(rates.rate("UAH", "UAH", date("2020-01-01")?)?, 1.0);
+
+    let result = rates.rate("EUR", "UAH", date("2024-10-31")?);
+    assert!(
+        matches!(result, Err(ExchangeError::MissingRate { .. })),
+        "Expected a missing rate but got {:?}",
+        result
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_inverse_and_cross_rates() -> anyhow::Result<()> {
+    let rates = ExchangeRates::from_toml(RATES)?;
+    let day = date("2024-12-01")?;
+
+    assert_eq!(rates.convert(442.0, "UAH", "EUR", day)?, 10.0);
+    assert_eq!(rates.convert(10.0, "USD", "EUR", day)?, 9.28);
+
+    let result = rates.rate("GBP", "EUR", day);
+    assert!(
+        matches!(&result, Err(ExchangeError::UnknownCurrency(code)) if code == "GBP"),
+        "Expected an unknown currency but got {:?}",
+        result
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_json_rates_and_validation() -> anyhow::Result<()> {
+    let rates = ExchangeRates::from_json(
+        r#"{"rates": [{"from": "USD", "to": "EUR", "rate": 0.92, "effective": "2024-11-01"}]}"#,
+    )?;
+    assert_eq!(rates.rates.len(), 1);
+    assert_eq!(
+        rates.convert(100.0, "USD", "EUR", date("2024-11-01")?)?,
+        92.0
+    );
+
+    let invalid = ExchangeRates::from_json(
+        r#"{"rates": [{"from": "USD", "to": "EUR", "rate": 0, "effective": "2024-11-01"}]}"#,
+    );
+    assert!(matches!(invalid, Err(ExchangeError::InvalidRate { .. })));
+
+    let invalid = ExchangeRates::from_toml("[[rates]]\nfrom = \"USD\"\n");
+    assert!(matches!(
+        invalid,
+        Err(ExchangeError::Config(ConfigError::Toml(_)))
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_rounding() {
+    assert_eq!(round_to(1.005, 2), 1.01);
+    assert_eq!(round_to(2.675, 2), 2.68);
+    assert_eq!(round_to(-1.005, 2), -1.01);
+    assert_eq!(round_to(6.787104072398, 2), 6.79);
+    assert_eq!(round_to(12.5, 0), 13.0);
+}
+
+#[test]
+fn test_convert_products() -> anyhow::Result<()> {
+    let rates = ExchangeRates::from_toml(RATES)?;
+    let file_content = fs::read_to_string("src/input.txt")?;
+    let (mut products, _) = parse_catalog(&file_content);
+
+    rates.convert_product(&mut products[0], "EUR", date("2024-12-01")?)?;
+    assert_eq!(products[0].price, 6.79);
+    assert_eq!(products[0].currency, Some(Currency::Eur));
+
+    products[1].currency = None;
+    let result = rates.convert_product(&mut products[1], "EUR", date("2024-12-01")?);
+    assert!(matches!(result, Err(ExchangeError::NoCurrency(_))));
+    assert_eq!(products[1].price, 349.99);
+
+    let failures = rates.convert_products(&mut products[1..], "EUR", date("2024-12-01")?);
+    assert_eq!(failures.len(), 1);
+    assert!(matches!(failures[0], (0, ExchangeError::NoCurrency(_))));
+    assert_eq!(products[1].price, 349.99);
+    assert!(products[2..]
+        .iter()
+        .all(|product| product.currency == Some(Currency::Eur)));
+
+    Ok(())
+}
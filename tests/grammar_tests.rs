@@ -48,7 +48,7 @@ fn test_products() -> anyhow::Result<()> {
 
     assert_eq!(product_pairs.len(), 7);
 
-    let product_1 = product_pairs.get(0).ok_or_else(|| anyhow!("1 product not found"))?;
+    let product_1 = product_pairs.first().ok_or_else(|| anyhow!("1 product not found"))?;
     let product_1_str = product_1.as_str();
     assert!(product_1_str.contains("Product 1"), "1 product is incorrect");
 
@@ -93,7 +93,7 @@ fn test_product() -> anyhow::Result<()> {
      2.	\"No excessive shine, perfect for autumn.\"
      *Availability*: true\n";
 
-    let pair = Grammar::parse(Rule::product, &input)?
+    let pair = Grammar::parse(Rule::product, input)?
         .next()
         .ok_or_else(|| anyhow!("no pair"))?;
 
@@ -167,6 +167,14 @@ fn test_price() -> anyhow::Result<()> {
     assert_eq!(pair.as_span().start(), 0);
     assert_eq!(pair.as_span().end(), 20);
 
+    let pair = Grammar::parse(Rule::price, "*Price*: $12.50\n")?
+        .next()
+        .ok_or_else(|| anyhow!("no pair"))?;
+
+    assert_eq!(pair.as_str(), "*Price*: $12.50\n");
+    assert_eq!(pair.as_span().start(), 0);
+    assert_eq!(pair.as_span().end(), 16);
+
     let pair = Grammar::parse(Rule::price, "299.99 UAH\n");
     assert!(pair.is_err(), "Expected error but got {:?}", pair);
 
@@ -176,6 +184,79 @@ fn test_price() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_discount() -> anyhow::Result<()> {
+    let pair = Grammar::parse(Rule::discount, "(was 349.99 UAH, -15%)")?
+        .next()
+        .ok_or_else(|| anyhow!("no pair"))?;
+
+    assert_eq!(pair.as_str(), "(was 349.99 UAH, -15%)");
+    let rules: Vec<Rule> = pair.into_inner().map(|p| p.as_rule()).collect();
+    assert!(rules.contains(&Rule::original_price));
+    assert!(rules.contains(&Rule::percentage));
+
+    let pair = Grammar::parse(Rule::discount, "(було 1 499,99 грн)")?
+        .next()
+        .ok_or_else(|| anyhow!("no pair"))?;
+    assert_eq!(pair.as_span().end(), pair.as_str().len());
+
+    let pair = Grammar::parse(Rule::discount, "(349.99 UAH)");
+    assert!(pair.is_err(), "Expected error but got {:?}", pair);
+
+    Ok(())
+}
+
+#[test]
+fn test_price_history() -> anyhow::Result<()> {
+    let input = "*Price History*: 2024-11-01: 349.99 UAH; 2025-01-15: ₴299.99\n";
+    let pair = Grammar::parse(Rule::price_history, input)?
+        .next()
+        .ok_or_else(|| anyhow!("no pair"))?;
+
+    assert_eq!(pair.as_str(), input);
+    let points = pair
+        .into_inner()
+        .filter(|p| p.as_rule() == Rule::price_points)
+        .flat_map(|p| p.into_inner())
+        .filter(|p| p.as_rule() == Rule::price_point)
+        .count();
+    assert_eq!(points, 2);
+
+    let pair = Grammar::parse(Rule::price_history, "*Price History*: 349.99 UAH\n");
+    assert!(pair.is_err(), "Expected error but got {:?}", pair);
+
+    Ok(())
+}
+
+#[test]
+fn test_size() -> anyhow::Result<()> {
+    for input in [
+        "*Size*: 50 ml\n",
+        "*Volume*: 1,5 l\n",
+        "*Size*: 1.7 fl oz\n",
+        "*Size*: 200G\n",
+        "*Об'єм*: 200 мл\n",
+    ] {
+        let input = labels::LabelSet::default().normalize(input);
+        let pair = Grammar::parse(Rule::size, &input.text)?
+            .next()
+            .ok_or_else(|| anyhow!("no pair"))?;
+        assert_eq!(pair.as_str(), input.text);
+    }
+
+    let pair = Grammar::parse(Rule::unit, "fl oz")?
+        .next()
+        .ok_or_else(|| anyhow!("no pair"))?;
+    assert_eq!(pair.as_str(), "fl oz");
+
+    let pair = Grammar::parse(Rule::size, "*Size*: 50 lb\n");
+    assert!(pair.is_err(), "Expected error but got {:?}", pair);
+    let pair = Grammar::parse(Rule::size, "*Size*: ml\n");
+    assert!(pair.is_err(), "Expected error but got {:?}", pair);
+
+    Ok(())
+}
+
 #[test]
 fn test_user_ratings() -> anyhow::Result<()> {
     let pair = Grammar::parse(Rule::user_ratings, "*User Ratings*: [5, 4, 5, 3, 4]\n")?
@@ -195,6 +276,104 @@ fn test_user_ratings() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_ukrainian_product() -> anyhow::Result<()> {
+    let english = "*Product 1*: Face Cream
+*Skin Type*: Dry Skin
+*Ingredients*: Water, Glycerin
+*Rating*: 4.5
+*Price*: 299.99 UAH
+*User Ratings*: [5, 4]
+*Recommendations*: Use in the morning.
+*Reviews*:
+1. \"Great!\"
+*Availability*: false
+";
+    let ukrainian = "*Товар 1*: Face Cream
+*Тип шкіри*: Dry Skin
+*Склад*: Water, Glycerin
+*Рейтинг*: 4.5
+*Ціна*: 299.99 UAH
+*Оцінки користувачів*: [5, 4]
+*Рекомендації*: Use in the morning.
+*Відгуки*:
+1. \"Great!\"
+*Наявність*: ні
+";
+
+    let (mut expected, errors) = parse_catalog(english);
+    assert!(errors.is_empty(), "unexpected errors {:?}", errors);
+    let (mut products, errors) = parse_catalog(&format!("{}{}", ukrainian, ukrainian));
+    assert!(errors.is_empty(), "unexpected errors {:?}", errors);
+    assert_eq!(products.len(), 2);
+
+    expected[0].source_map = None;
+    products[0].source_map = None;
+    assert_eq!(products[0], expected[0]);
+
+    let in_stock = ukrainian.replace("*Наявність*: ні", "*Наявність*: так");
+    let in_stock = labels::LabelSet::default().normalize(&in_stock);
+    let pair = Grammar::parse(Rule::product, &in_stock.text)?
+        .next()
+        .ok_or_else(|| anyhow!("no pair"))?;
+    assert!(Product::from_pair(pair).availability.is_in_stock());
+
+    let pair = Grammar::parse(Rule::skin_type, "*Тип шкіри*: Dry Skin\n");
+    assert!(pair.is_err(), "Expected error but got {:?}", pair);
+    let (products, errors) = parse_catalog(&ukrainian.replace("*Тип шкіри*", "*Тип шкіры*"));
+    assert!(products.is_empty());
+    assert_eq!(errors.len(), 1);
+
+    Ok(())
+}
+
+#[test]
+fn test_extra_fields() -> anyhow::Result<()> {
+    let file_content = fs::read_to_string("src/input.txt")?;
+    let block = product_blocks(&file_content)[0].text;
+    let input = block
+        .replace("*Skin Type*", "*Brand*: Acme\n*Skin Type*")
+        .replace("*User Ratings*", "*Texture*: Cream\n*SPF*: 30\n*User Ratings*")
+        .replace("*Availability*: false", "*Availability*: false\n*Made In*: Ukraine");
+
+    let (products, errors) = parse_catalog(&input);
+    assert!(errors.is_empty(), "unexpected errors {:?}", errors);
+    let extra: Vec<(&str, &str)> = products[0]
+        .extra
+        .iter()
+        .map(|(label, value)| (label.as_str(), value.as_str()))
+        .collect();
+    assert_eq!(
+        extra,
+        vec![
+            ("Brand", "Acme"),
+            ("Made In", "Ukraine"),
+            ("SPF", "30"),
+            ("Texture", "Cream")
+        ]
+    );
+    assert_eq!(products[0].skin_type, "Dry Skin");
+
+    let json = serde_json::to_value(&products[0])?;
+    assert_eq!(json["extra"]["Texture"], "Cream");
+
+    let source_map = products[0]
+        .source_map
+        .as_ref()
+        .ok_or_else(|| anyhow!("no source map"))?;
+    let span = source_map
+        .extra
+        .get("SPF")
+        .ok_or_else(|| anyhow!("no SPF span"))?;
+    assert_eq!(span.line, 8);
+    assert_eq!(&input[span.start..span.end], "30");
+
+    let pair = Grammar::parse(Rule::extra_field, "*Rating*: 4.5\n");
+    assert!(pair.is_err(), "Expected error but got {:?}", pair);
+
+    Ok(())
+}
+
 #[test]
 fn test_product_name() -> anyhow::Result<()> {
     let pair = Grammar::parse(
@@ -220,6 +399,25 @@ fn test_product_name() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_product_id() -> anyhow::Result<()> {
+    let pair = Grammar::parse(Rule::product_id, "42")?
+        .next()
+        .ok_or_else(|| anyhow!("no pair"))?;
+
+    assert_eq!(pair.as_str(), "42");
+    assert_eq!(pair.as_span().start(), 0);
+    assert_eq!(pair.as_span().end(), 2);
+
+    let pair = Grammar::parse(Rule::product_name, "*Product 4 2*: Face Cream\n");
+    assert!(pair.is_err(), "Expected error but got {:?}", pair);
+
+    let pair = Grammar::parse(Rule::product_id, "");
+    assert!(pair.is_err(), "Expected error but got {:?}", pair);
+
+    Ok(())
+}
+
 #[test]
 fn test_recommendations() -> anyhow::Result<()> {
     let pair = Grammar::parse(Rule::recommendations, "*Recommendations*: Use in the morning and evening after cleansing the skin. Suitable for sensitive skin.\n")?
@@ -358,6 +556,18 @@ fn test_number_list() -> anyhow::Result<()> {
     assert_eq!(pair.as_span().start(), 0);
     assert_eq!(pair.as_span().end(), 15);
 
+    let pair = Grammar::parse(Rule::number_list, "[4,5; 5; 3,5]")?
+        .next()
+        .ok_or_else(|| anyhow!("no pair"))?;
+
+    assert_eq!(pair.as_str(), "[4,5; 5; 3,5]");
+    let amounts: Vec<&str> = pair
+        .into_inner()
+        .filter(|p| p.as_rule() == Rule::amount)
+        .map(|p| p.as_str())
+        .collect();
+    assert_eq!(amounts, vec!["4,5", "5", "3,5"]);
+
     let pair = Grammar::parse(Rule::number_list, "5 4 5 3 4");
     assert!(pair.is_err(), "Expected error but got {:?}", pair);
 
@@ -367,6 +577,40 @@ fn test_number_list() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_amount() -> anyhow::Result<()> {
+    for amount in [
+        "299.99",
+        "299,99",
+        "1 299,99",
+        "1\u{a0}299,99",
+        "1.299,99",
+        "1,299.99",
+        "12",
+    ] {
+        let pair = Grammar::parse(Rule::amount, amount)?
+            .next()
+            .ok_or_else(|| anyhow!("no pair"))?;
+
+        assert_eq!(pair.as_str(), amount);
+        assert_eq!(pair.as_span().start(), 0);
+        assert_eq!(pair.as_span().end(), amount.len());
+    }
+
+    let pair = Grammar::parse(Rule::amount, "1 29,99")?
+        .next()
+        .ok_or_else(|| anyhow!("no pair"))?;
+    assert_eq!(pair.as_str(), "1");
+
+    let pair = Grammar::parse(Rule::amount, ",99");
+    assert!(pair.is_err(), "Expected error but got {:?}", pair);
+
+    let pair = Grammar::parse(Rule::amount, "");
+    assert!(pair.is_err(), "Expected error but got {:?}", pair);
+
+    Ok(())
+}
+
 #[test]
 fn test_single_review() -> anyhow::Result<()> {
     let pair = Grammar::parse(
@@ -395,6 +639,40 @@ fn test_single_review() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_review_stars() -> anyhow::Result<()> {
+    let pair = Grammar::parse(Rule::review, "1. (4.5/5) \"Absorbs easily!\"\n")?
+        .next()
+        .ok_or_else(|| anyhow!("no pair"))?;
+
+    let stars = pair
+        .into_inner()
+        .find(|p| p.as_rule() == Rule::review_stars)
+        .ok_or_else(|| anyhow!("no review_stars pair"))?;
+    assert_eq!(stars.as_str(), "(4.5/5)");
+
+    let pair = Grammar::parse(Rule::review_stars, "(5/5)")?
+        .next()
+        .ok_or_else(|| anyhow!("no pair"))?;
+    assert_eq!(pair.as_span().end(), 5);
+
+    let pair = Grammar::parse(Rule::review, "1. (5/5)\n")?
+        .next()
+        .ok_or_else(|| anyhow!("no pair"))?;
+    assert_eq!(pair.as_str(), "1. (5/5)\n");
+    assert!(pair.into_inner().any(|p| p.as_rule() == Rule::review_stars));
+
+    let pair = Grammar::parse(Rule::review, "1. (Edited) \"Absorbs easily!\"\n")?
+        .next()
+        .ok_or_else(|| anyhow!("no pair"))?;
+    assert!(pair.into_inner().all(|p| p.as_rule() != Rule::review_stars));
+
+    let pair = Grammar::parse(Rule::review_stars, "(5)");
+    assert!(pair.is_err(), "Expected error but got {:?}", pair);
+
+    Ok(())
+}
+
 #[test]
 fn test_currency() -> anyhow::Result<()> {
     let pair = Grammar::parse(Rule::currency, "UAH")?
@@ -421,7 +699,15 @@ fn test_currency() -> anyhow::Result<()> {
     assert_eq!(pair.as_span().start(), 0);
     assert_eq!(pair.as_span().end(), 3);
 
-    let pair = Grammar::parse(Rule::currency, "IDR");
+    let pair = Grammar::parse(Rule::currency, "€")?
+        .next()
+        .ok_or_else(|| anyhow!("no pair"))?;
+
+    assert_eq!(pair.as_str(), "€");
+    assert_eq!(pair.as_span().start(), 0);
+    assert_eq!(pair.as_span().end(), 3);
+
+    let pair = Grammar::parse(Rule::currency, "12");
     assert!(pair.is_err(), "Expected error but got {:?}", pair);
 
     let pair = Grammar::parse(Rule::currency, "");
@@ -459,6 +745,19 @@ fn test_bool() -> anyhow::Result<()> {
     assert_eq!(pair.as_span().start(), 0);
     assert_eq!(pair.as_span().end(), 4);
 
+    let pair = Grammar::parse(Rule::boolean, "так");
+    assert!(pair.is_err(), "Expected error but got {:?}", pair);
+    let availability = labels::LabelSet::default().normalize("*Наявність*: так\n");
+    assert_eq!(availability.text, "*Availability*: true\n");
+    let pair = Grammar::parse(Rule::availability, &availability.text)?
+        .next()
+        .ok_or_else(|| anyhow!("no pair"))?;
+    let value = pair
+        .into_inner()
+        .flatten()
+        .find(|p| p.as_rule() == Rule::yes);
+    assert!(value.is_some());
+
     let pair = Grammar::parse(Rule::boolean, "gbehfhr");
     assert!(pair.is_err(), "Expected error but got {:?}", pair);
 
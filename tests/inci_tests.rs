@@ -0,0 +1,100 @@
+use cosmetics_parser::config;
+use cosmetics_parser::inci::*;
+use cosmetics_parser::*;
+use std::fs;
+
+#[test]
+fn test_lookup() -> anyhow::Result<()> {
+    let dictionary = InciDictionary::builtin();
+    let cases = [
+        ("Water", Some("Aqua")),
+        ("Shea Butter", Some("Butyrospermum Parkii Butter")),
+        ("vitamin-e", Some("Tocopherol")),
+        ("Aloe Vera", Some("Aloe Barbadensis Leaf Juice")),
+        ("Glycerin", Some("Glycerin")),
+        ("Cera Alba", Some("Cera Alba")),
+        ("Вода", Some("Aqua")),
+        ("BHA", Some("BHA")),
+        ("Butylated Hydroxyanisole", Some("BHA")),
+        ("Salicylic Acid", Some("Salicylic Acid")),
+        ("Peptides", None),
+        ("", None),
+    ];
+    for (name, inci) in cases {
+        assert_eq!(dictionary.lookup(name), inci, "{}", name);
+    }
+
+    let ingredients = dictionary.normalize("Water,  Peptides , Vitamin C,");
+    assert_eq!(
+        ingredients,
+        vec![
+            Ingredient {
+                name: String::from("Water"),
+                inci: Some(String::from("Aqua")),
+            },
+            Ingredient {
+                name: String::from("Peptides"),
+                inci: None,
+            },
+            Ingredient {
+                name: String::from("Vitamin C"),
+                inci: Some(String::from("Ascorbic Acid")),
+            },
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_extend() -> anyhow::Result<()> {
+    let mut dictionary = InciDictionary::builtin();
+    dictionary.extend(config::from_toml::<InciDictionary>(
+        "[[synonyms]]\ninci = \"Palmitoyl Tripeptide-1\"\nnames = [\"Peptides\"]\n\n\
+         [[synonyms]]\ninci = \"Sodium Hyaluronate\"\nnames = [\"Hyaluronic Acid\"]\n",
+    )?);
+    assert_eq!(
+        dictionary.lookup("Peptides"),
+        Some("Palmitoyl Tripeptide-1")
+    );
+    assert_eq!(
+        dictionary.lookup("Hyaluronic Acid"),
+        Some("Sodium Hyaluronate")
+    );
+    assert_eq!(dictionary.lookup("Water"), Some("Aqua"));
+
+    let json = config::from_json::<InciDictionary>(r#"{ "synonyms": [{ "inci": "Urea" }] }"#)?;
+    assert_eq!(json.lookup("UREA"), Some("Urea"));
+    assert!(config::from_toml::<InciDictionary>("[[synonyms]]\nnames = []\n").is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_annotate() -> anyhow::Result<()> {
+    let file_content = fs::read_to_string("src/input.txt")?;
+    let (mut products, errors) = parse_catalog(&file_content);
+    assert!(errors.is_empty(), "unexpected errors {:?}", errors);
+    assert!(products[0].inci.is_empty());
+
+    InciDictionary::builtin().annotate(&mut products);
+    let unmapped: Vec<&str> = products
+        .iter()
+        .flat_map(|p| &p.inci)
+        .filter(|i| i.inci.is_none())
+        .map(|i| i.name.as_str())
+        .collect();
+    assert_eq!(unmapped, vec!["Peptides"]);
+    let names: Vec<&str> = products[0].inci.iter().map(|i| i.name.as_str()).collect();
+    assert_eq!(names.join(", "), products[0].ingredients);
+
+    let json = serde_json::to_value(&products[4])?;
+    assert_eq!(
+        json["inci"][1],
+        serde_json::json!({ "name": "Peptides", "inci": null })
+    );
+    let product: Product = serde_json::from_value(json)?;
+    assert_eq!(product.inci, products[4].inci);
+
+    Ok(())
+}
@@ -0,0 +1,166 @@
+use cosmetics_parser::config;
+use cosmetics_parser::labels::*;
+use cosmetics_parser::product_ref::ProductRef;
+use cosmetics_parser::reader::ProductReader;
+use cosmetics_parser::*;
+
+const UKRAINIAN: &str = "*Товар 1*: Face Cream
+*Тип шкіри*: Dry Skin
+*Склад*: Water, Glycerin
+*Рейтинг*: 4.5
+*Ціна*: 299.99 UAH
+*Оцінки користувачів*: [5, 4]
+*Рекомендації*: Use in the morning.
+*Відгуки*:
+1. \"Great!\"
+*Наявність*: так
+";
+
+#[test]
+fn test_product_label_prefix() {
+    let labels = LabelSet::default();
+
+    assert!(labels.is_product_label("*Product 1*: Face Cream\n"));
+    assert!(labels.is_product_label("  *Товар 2*: Face Cream\n"));
+    assert!(labels.is_product_label("*Продукт 3*: Face Cream\n"));
+    assert!(!labels.is_product_label("*Products*: 3\n"));
+    assert!(!labels.is_product_label("*Тип шкіри*: Dry Skin\n"));
+    assert!(!labels.is_product_label("Product 1: Face Cream\n"));
+}
+
+#[test]
+fn test_normalize() {
+    let labels = LabelSet::default();
+
+    let english = "*Product 1*: Face Cream\n*Volume*: 50 ml\n";
+    let normalized = labels.normalize(english);
+    assert_eq!(normalized.text, "*Product 1*: Face Cream\n*Size*: 50 ml\n");
+
+    let normalized = labels.normalize("*Product 1*: Face Cream\n*Price*: 10 UAH\n");
+    assert!(matches!(normalized.text, std::borrow::Cow::Borrowed(_)));
+
+    let normalized = labels.normalize("*Наявність*: передзамовлення до 2026-12-01\n");
+    assert_eq!(
+        normalized.text,
+        "*Availability*: pre-order until 2026-12-01\n"
+    );
+    let normalized = labels.normalize("*Наявність*: нісенітниця\n");
+    assert_eq!(normalized.text, "*Availability*: нісенітниця\n");
+
+    let normalized = labels.normalize(UKRAINIAN);
+    assert!(normalized
+        .text
+        .starts_with("*Product 1*: Face Cream\n*Skin Type*: Dry Skin\n"));
+    let rating = normalized.text.find("4.5").unwrap_or_default();
+    assert_eq!(&UKRAINIAN[normalized.original_offset(rating)..][..3], "4.5");
+}
+
+#[test]
+fn test_custom_labels() -> anyhow::Result<()> {
+    let labels = config::from_toml::<LabelSet>(
+        r#"
+        [[labels]]
+        name = "Product"
+        aliases = ["Produkt"]
+
+        [[labels]]
+        name = "Skin Type"
+        aliases = ["Typ skóry"]
+
+        [[labels]]
+        name = "Price"
+        aliases = ["Cena"]
+
+        [[labels]]
+        name = "Availability"
+        aliases = ["Dostępność"]
+
+        [[values]]
+        name = "in stock"
+        aliases = ["dostępny"]
+        "#,
+    )?;
+    let options = ParseOptions {
+        labels: labels.clone(),
+        ..ParseOptions::default()
+    };
+    let polish = UKRAINIAN
+        .replace("*Товар 1*", "*Produkt 1*")
+        .replace("*Тип шкіри*", "*Typ skóry*")
+        .replace("*Ціна*", "*Cena*")
+        .replace("*Наявність*: так", "*Dostępność*: dostępny (7)");
+
+    let (products, errors) = parse_catalog_with(&format!("{}{}", polish, UKRAINIAN), &options);
+    assert!(errors.is_empty(), "unexpected errors {:?}", errors);
+    assert_eq!(products.len(), 2);
+    assert_eq!(products[0].skin_type, "Dry Skin");
+    assert_eq!(products[0].price, 299.99);
+    assert_eq!(products[0].availability.quantity(), Some(7));
+    let block = product_blocks_with(&polish, &labels)[0];
+    let product = ProductRef::parse_with(&block, &options)?;
+    assert_eq!(product.skin_type, "Dry Skin");
+    assert!(ProductRef::parse(&block).is_err());
+
+    let (products, errors) = parse_catalog(&polish);
+    assert!(products.is_empty());
+    assert_eq!(errors.len(), 1);
+
+    let input = format!("{}{}", polish, polish);
+    let reader = ProductReader::with_options(input.as_bytes(), options);
+    assert_eq!(reader.filter(Result::is_ok).count(), 2);
+
+    Ok(())
+}
+
+#[test]
+fn test_spans_of_normalized_labels() -> anyhow::Result<()> {
+    let (products, errors) = parse_catalog(UKRAINIAN);
+    assert!(errors.is_empty(), "unexpected errors {:?}", errors);
+    let source_map = products[0]
+        .source_map
+        .as_ref()
+        .ok_or_else(|| anyhow::anyhow!("no source map"))?;
+
+    let price = source_map
+        .field("price")
+        .ok_or_else(|| anyhow::anyhow!("no price span"))?;
+    assert_eq!(&UKRAINIAN[price.start..price.end], "299.99");
+    assert_eq!((price.line, price.column), (5, 9));
+
+    let name = source_map
+        .field("product_name")
+        .ok_or_else(|| anyhow::anyhow!("no name span"))?;
+    assert_eq!(&UKRAINIAN[name.start..name.end], "Face Cream");
+
+    let input = UKRAINIAN.replace("*Наявність*: так", "*Наявність*: в наявності (12)");
+    let (products, errors) = parse_catalog(&input);
+    assert!(errors.is_empty(), "unexpected errors {:?}", errors);
+    let availability = products[0]
+        .source_map
+        .as_ref()
+        .and_then(|map| map.field("availability"))
+        .ok_or_else(|| anyhow::anyhow!("no availability span"))?;
+    assert_eq!(
+        &input[availability.start..availability.end],
+        "в наявності (12)"
+    );
+    assert_eq!(availability.column, 14);
+
+    Ok(())
+}
+
+#[test]
+fn test_product_ref_with_labels() -> anyhow::Result<()> {
+    let block = product_blocks(UKRAINIAN)[0];
+    let product = ProductRef::parse(&block)?;
+
+    assert_eq!(product.skin_type, "Dry Skin");
+    assert_eq!(product.recommendations, "Use in the morning.");
+    assert_eq!(product.reviews, vec!["1. \"Great!\""]);
+    assert!(std::ptr::eq(
+        product.ingredients.as_ptr(),
+        UKRAINIAN[UKRAINIAN.find("Water").unwrap_or_default()..].as_ptr()
+    ));
+
+    Ok(())
+}
@@ -0,0 +1,171 @@
+use chrono::NaiveDate;
+use cosmetics_parser::currency::Currency;
+use cosmetics_parser::exchange::ExchangeRates;
+use cosmetics_parser::merge::*;
+use cosmetics_parser::*;
+use std::fs;
+
+fn catalogs() -> anyhow::Result<Vec<Vec<Product>>> {
+    let file_content = fs::read_to_string("src/input.txt")?;
+    let (first, _) = parse_catalog(&file_content);
+
+    let mut second: Vec<Product> = first[..2].to_vec();
+    second[0].product_id = Some(10);
+    second[0].price = 279.99;
+    second[0].user_ratings = vec![5.0, 5.0, 2.0];
+    second[0].reviews.push(String::from("2. \"Great value\""));
+    second[1].product_name = second[1].product_name.to_uppercase();
+    let mut new = first[0].clone();
+    new.product_name = String::from("Toner \"Fresh\"");
+    second.push(new);
+
+    Ok(vec![first, second])
+}
+
+#[test]
+fn test_first_and_last_wins() -> anyhow::Result<()> {
+    let catalogs = catalogs()?;
+
+    let report = merge_catalogs(&catalogs, ConflictPolicy::FirstWins)?;
+    assert_eq!(report.products.len(), catalogs[0].len() + 1);
+    assert_eq!(report.products[0].price, 299.99);
+    assert_eq!(report.products[0].product_id, Some(1));
+    assert_eq!(
+        report.products.last().map(|p| p.product_name.as_str()),
+        Some("Toner \"Fresh\"")
+    );
+
+    let fields: Vec<&str> = report.conflicts.iter().map(|c| c.field.as_str()).collect();
+    assert_eq!(
+        fields,
+        vec!["price", "user_ratings", "reviews", "product_name"]
+    );
+    assert_eq!(
+        report.conflicts[0].product_name,
+        "Face Cream \"Moisturizing\""
+    );
+    assert_eq!(
+        report.conflicts[0].values,
+        vec![
+            ConflictValue {
+                catalog: 0,
+                value: serde_json::json!(299.99)
+            },
+            ConflictValue {
+                catalog: 1,
+                value: serde_json::json!(279.99)
+            },
+        ]
+    );
+    assert_eq!(
+        report.conflicts[0].resolution,
+        Resolution::Kept { catalog: 0 }
+    );
+
+    let report = merge_catalogs(&catalogs, ConflictPolicy::LastWins)?;
+    assert_eq!(report.products[0].price, 279.99);
+    assert_eq!(report.products[0].product_id, Some(10));
+    assert!(report
+        .conflicts
+        .iter()
+        .all(|c| c.resolution == Resolution::Kept { catalog: 1 }));
+
+    Ok(())
+}
+
+#[test]
+fn test_lowest_price() -> anyhow::Result<()> {
+    let mut catalogs = catalogs()?;
+    catalogs[1][1].price = 999.0;
+
+    let report = merge_catalogs(&catalogs, ConflictPolicy::LowestPrice)?;
+
+    assert_eq!(report.products[0].price, 279.99);
+    assert_eq!(report.products[0].user_ratings, vec![5.0, 5.0, 2.0]);
+    assert_eq!(report.products[1].price, 349.99);
+    assert_eq!(report.products[1].product_name, catalogs[0][1].product_name);
+
+    Ok(())
+}
+
+#[test]
+fn test_lowest_price_across_currencies() -> anyhow::Result<()> {
+    let mut catalogs = catalogs()?;
+    catalogs[1][0].price = 7.5;
+    catalogs[1][0].currency = Some(Currency::Usd);
+
+    let error = merge_catalogs(&catalogs, ConflictPolicy::LowestPrice).err();
+    assert_eq!(
+        error.map(|e| e.to_string()),
+        Some(String::from("cannot pick the lowest price of Face Cream \"Moisturizing\" from prices in UAH, USD; convert the catalogs to one currency first"))
+    );
+
+    let rates = ExchangeRates::from_toml(
+        "[[rates]]\nfrom = \"USD\"\nto = \"UAH\"\nrate = 41.0\neffective = \"2024-11-01\"\n",
+    )?;
+    let date = NaiveDate::from_ymd_opt(2025, 1, 1).ok_or_else(|| anyhow::anyhow!("bad date"))?;
+    for catalog in &mut catalogs {
+        for product in catalog {
+            rates.convert_product(product, "UAH", date)?;
+        }
+    }
+    let report = merge_catalogs(&catalogs, ConflictPolicy::LowestPrice)?;
+    assert_eq!(report.products[0].price, 299.99);
+    assert_eq!(report.products[0].currency, Some(Currency::Uah));
+
+    Ok(())
+}
+
+#[test]
+fn test_union_of_reviews_and_ratings() -> anyhow::Result<()> {
+    let catalogs = catalogs()?;
+
+    let report = merge_catalogs(&catalogs, ConflictPolicy::Union)?;
+
+    let product = &report.products[0];
+    assert_eq!(product.price, 299.99);
+    assert_eq!(product.user_ratings, vec![5.0, 4.0, 5.0, 3.0, 4.0, 2.0]);
+    assert_eq!(product.reviews.len(), 2);
+    assert_eq!(product.reviews[1], "2. \"Great value\"");
+
+    let resolutions: Vec<Resolution> = report.conflicts.iter().map(|c| c.resolution).collect();
+    assert_eq!(
+        resolutions,
+        vec![
+            Resolution::Kept { catalog: 0 },
+            Resolution::Merged,
+            Resolution::Merged,
+            Resolution::Kept { catalog: 0 },
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_fail_on_conflict() -> anyhow::Result<()> {
+    let catalogs = catalogs()?;
+
+    let result = merge_catalogs(&catalogs, ConflictPolicy::Fail);
+    let Err(MergeError::Conflict(conflict)) = result else {
+        panic!("Expected a conflict but got {:?}", result);
+    };
+    assert_eq!(conflict.field, "price");
+
+    let same = vec![catalogs[0].clone(), catalogs[0].clone()];
+    let report = merge_catalogs(&same, ConflictPolicy::Fail)?;
+    assert_eq!(report.products, catalogs[0]);
+    assert!(report.conflicts.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn test_policy_names() -> anyhow::Result<()> {
+    for policy in ConflictPolicy::ALL {
+        assert_eq!(policy.name().parse::<ConflictPolicy>()?, policy);
+    }
+    assert!("cheapest".parse::<ConflictPolicy>().is_err());
+
+    Ok(())
+}
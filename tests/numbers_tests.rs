@@ -0,0 +1,147 @@
+use cosmetics_parser::numbers::*;
+use cosmetics_parser::parallel::parse_catalog_parallel_with;
+use cosmetics_parser::reader::ProductReader;
+use cosmetics_parser::*;
+use std::io::Cursor;
+
+const CATALOG: &str = "*Product 1*: Gesichtscreme
+*Skin Type*: Dry Skin
+*Ingredients*: Water, Glycerin
+*Rating*: 4
+*Price*: 1.299 €
+*User Ratings*: [5, 4]
+*Recommendations*: Morgens auftragen.
+*Reviews*:
+1. \"Gut\"
+*Availability*: true
+
+*Product 2*: Serum
+*Skin Type*: Oily Skin
+*Ingredients*: Retinol
+*Rating*: 4,5
+*Price*: 1 299,99 €
+*User Ratings*: [4,5; 5; 3,5]
+*Recommendations*: Abends.
+*Reviews*:
+1. \"Super\"
+*Availability*: true
+";
+
+#[test]
+fn test_evidence() {
+    assert_eq!(
+        NumberFormat::evidence("299,99"),
+        Some(NumberFormat::DecimalComma)
+    );
+    assert_eq!(
+        NumberFormat::evidence("1.299,99"),
+        Some(NumberFormat::DecimalComma)
+    );
+    assert_eq!(
+        NumberFormat::evidence("1,299.99"),
+        Some(NumberFormat::DecimalPoint)
+    );
+    assert_eq!(
+        NumberFormat::evidence("1.299.000"),
+        Some(NumberFormat::DecimalComma)
+    );
+    assert_eq!(
+        NumberFormat::evidence("299.99"),
+        Some(NumberFormat::DecimalPoint)
+    );
+    assert_eq!(NumberFormat::evidence("1.299"), None);
+    assert_eq!(NumberFormat::evidence("1 299"), None);
+    assert_eq!(NumberFormat::evidence("12"), None);
+}
+
+#[test]
+fn test_parse_amounts() {
+    let comma = NumberFormat::DecimalComma;
+    let point = NumberFormat::DecimalPoint;
+
+    assert_eq!(comma.parse("1 299,99"), Some(1299.99));
+    assert_eq!(comma.parse("1\u{a0}299,99"), Some(1299.99));
+    assert_eq!(comma.parse("1\u{202f}299,99"), Some(1299.99));
+    assert_eq!(comma.parse("1.299,99"), Some(1299.99));
+    assert_eq!(point.parse("1,299.99"), Some(1299.99));
+    assert_eq!(point.parse("1'299.99"), Some(1299.99));
+
+    assert_eq!(comma.parse("1.299"), Some(1299.0));
+    assert_eq!(point.parse("1.299"), Some(1.299));
+    assert_eq!(point.parse("299,99"), Some(299.99));
+    assert_eq!(comma.parse("-4,5"), Some(-4.5));
+}
+
+#[test]
+fn test_catalog_detection() -> anyhow::Result<()> {
+    let (products, errors) = parse_catalog(CATALOG);
+
+    assert!(errors.is_empty(), "{:?}", errors);
+    assert_eq!(products[0].price, 1299.0);
+    assert_eq!(products[1].price, 1299.99);
+    assert_eq!(products[1].rating, 4.5);
+    assert_eq!(products[1].user_ratings, vec![4.5, 5.0, 3.5]);
+    assert_eq!(products[0].user_ratings, vec![5.0, 4.0]);
+
+    let options = ParseOptions::default();
+    let (parallel, _) = parse_catalog_parallel_with(CATALOG, &options);
+    assert_eq!(parallel, products);
+
+    let first = parse_product(&product_blocks(CATALOG)[0])?;
+    assert_eq!(first.price, 1.299);
+
+    Ok(())
+}
+
+#[test]
+fn test_selected_format() -> anyhow::Result<()> {
+    let options = ParseOptions {
+        numbers: Some("en".parse()?),
+        ..ParseOptions::default()
+    };
+    let (products, errors) = parse_catalog_with(CATALOG, &options);
+
+    assert!(errors.is_empty(), "{:?}", errors);
+    assert_eq!(products[0].price, 1.299);
+    assert_eq!(products[1].price, 1299.99);
+
+    let streamed = ProductReader::with_options(Cursor::new(CATALOG.as_bytes()), options)
+        .collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(streamed, products);
+
+    assert!("fr".parse::<NumberFormat>().is_ok());
+    assert!("xx".parse::<NumberFormat>().is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_ambiguous_number_list() -> anyhow::Result<()> {
+    let input = CATALOG.replace("[5, 4]", "[4,5]");
+    let (products, errors) = parse_catalog(&input);
+
+    assert_eq!(products.len(), 1);
+    assert!(
+        matches!(&errors[..], [ParseError::Syntax { line: 6, message }] if message.contains("[4,5]")),
+        "{:?}",
+        errors
+    );
+    assert!(product_ref::ProductRef::parse_with(
+        &product_blocks(&input)[0],
+        &ParseOptions {
+            numbers: Some(NumberFormat::DecimalComma),
+            ..ParseOptions::default()
+        }
+    )
+    .is_err());
+
+    let options = ParseOptions {
+        numbers: Some(NumberFormat::DecimalPoint),
+        ..ParseOptions::default()
+    };
+    let (products, errors) = parse_catalog_with(&input, &options);
+    assert!(errors.is_empty(), "{:?}", errors);
+    assert_eq!(products[0].user_ratings, vec![4.0, 5.0]);
+
+    Ok(())
+}
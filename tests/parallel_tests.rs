@@ -0,0 +1,65 @@
+use cosmetics_parser::config;
+use cosmetics_parser::numbers::NumberFormat;
+use cosmetics_parser::parallel::*;
+use cosmetics_parser::*;
+use std::fs;
+
+#[test]
+fn test_parallel_matches_sequential() -> anyhow::Result<()> {
+    let file_content = fs::read_to_string("src/input.txt")?.repeat(50);
+
+    let (sequential, _) = parse_catalog(&file_content);
+    let (parallel, errors) =
+        parse_catalog_with_threads(&file_content, &ParseOptions::default(), 4)?;
+
+    assert!(errors.is_empty(), "unexpected errors {:?}", errors);
+    assert_eq!(parallel.len(), 350);
+    assert_eq!(
+        serde_json::to_value(&parallel)?,
+        serde_json::to_value(&sequential)?
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_threads_with_options() -> anyhow::Result<()> {
+    let file_content = fs::read_to_string("src/input.txt")?
+        .replace("*Price*: 299.99 UAH", "*Price*: 1.299 ZZZ")
+        .replace("*Rating*:", "*Ocena*:");
+    let options = ParseOptions {
+        numbers: Some(NumberFormat::DecimalComma),
+        currencies: config::from_toml("[[currencies]]\ncode = \"ZZZ\"\n")?,
+        labels: config::from_toml("[[labels]]\nname = \"Rating\"\naliases = [\"Ocena\"]\n")?,
+    };
+
+    let (sequential, _) = parse_catalog_with(&file_content, &options);
+    let (parallel, errors) = parse_catalog_with_threads(&file_content, &options, 2)?;
+
+    assert!(errors.is_empty(), "unexpected errors {:?}", errors);
+    assert_eq!(parallel.len(), 7);
+    assert_eq!(parallel[0].price, 1299.0);
+    assert_eq!(
+        serde_json::to_value(&parallel)?,
+        serde_json::to_value(&sequential)?
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_parallel_keeps_error_order() -> anyhow::Result<()> {
+    let file_content = fs::read_to_string("src/input.txt")?;
+    let broken = "*Product 8*: Toner\n*Rating*: 4.1\n\n";
+    let input = format!("{}{}{}{}", broken, file_content, broken, file_content);
+
+    let (products, errors) = parse_catalog_parallel(&input);
+
+    assert_eq!(products.len(), 14);
+    let lines: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].starts_with("line 2:"), "got {}", lines[0]);
+    assert!(lines[1].starts_with("line 88:"), "got {}", lines[1]);
+
+    Ok(())
+}
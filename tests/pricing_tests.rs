@@ -0,0 +1,131 @@
+mod common;
+
+use chrono::NaiveDate;
+use common::with_price;
+use cosmetics_parser::currency::Currency;
+use cosmetics_parser::exchange::ExchangeRates;
+use cosmetics_parser::pricing::*;
+use cosmetics_parser::*;
+
+#[test]
+fn test_discount() -> anyhow::Result<()> {
+    let (products, errors) = parse_catalog(&with_price("299.99 UAH (was 349.99 UAH, -15%)")?);
+    assert!(errors.is_empty(), "unexpected errors {:?}", errors);
+
+    let pricing = products[0].pricing();
+    assert_eq!(pricing.current, 299.99);
+    assert_eq!(pricing.currency, Some(Currency::Uah));
+    assert_eq!(pricing.original, Some(349.99));
+    assert_eq!(pricing.discount_percent, Some(14.29));
+
+    let cases = [
+        ("299.99 (was 349.99)", 349.99),
+        ("₴299.99 (was ₴349.99, -14.29%)", 349.99),
+        ("1 299,99 грн (було 1 499,99 грн, -13%)", 1499.99),
+    ];
+    for (price, original) in cases {
+        let (products, errors) = parse_catalog(&with_price(price)?);
+        assert!(errors.is_empty(), "{}: {:?}", price, errors);
+        assert_eq!(products[0].original_price, Some(original), "{}", price);
+    }
+
+    let (products, _) = parse_catalog(&with_price("299.99 UAH")?);
+    assert_eq!(products[0].pricing().discount_percent, None);
+
+    Ok(())
+}
+
+#[test]
+fn test_invalid_discount() -> anyhow::Result<()> {
+    let cases = [
+        (
+            "299.99 UAH (was 349.99 UAH, -30%)",
+            "line 5: stated discount 30% does not match the computed 14.29%",
+        ),
+        (
+            "299.99 UAH (was 249.99 UAH)",
+            "line 5: original price 249.99 is not above the price 299.99",
+        ),
+        (
+            "299.99 UAH (was 349.99 EUR)",
+            "line 5: original price is in EUR, but the price is in UAH",
+        ),
+        (
+            "299.99 UAH (was 349.99 XYZ)",
+            "line 5: unknown currency `XYZ`",
+        ),
+    ];
+    for (price, message) in cases {
+        let (_, errors) = parse_catalog(&with_price(price)?);
+        assert_eq!(errors.len(), 1, "{}", price);
+        assert_eq!(errors[0].to_string(), message);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_price_history() -> anyhow::Result<()> {
+    let input = with_price(
+        "299.99 UAH (was 349.99 UAH)\n*Price History*: 2024-11-01: 349.99 UAH; 2025-01-15: 299.99",
+    )?;
+    let (products, errors) = parse_catalog(&input);
+    assert!(errors.is_empty(), "unexpected errors {:?}", errors);
+
+    assert_eq!(
+        products[0].price_history,
+        vec![
+            PricePoint {
+                date: NaiveDate::from_ymd_opt(2024, 11, 1).unwrap_or_default(),
+                price: 349.99,
+                currency: Some(Currency::Uah),
+            },
+            PricePoint {
+                date: NaiveDate::from_ymd_opt(2025, 1, 15).unwrap_or_default(),
+                price: 299.99,
+                currency: None,
+            },
+        ]
+    );
+
+    let json = serde_json::to_value(&products[0])?;
+    assert_eq!(json["price_history"][0]["date"], "2024-11-01");
+    let product: Product = serde_json::from_value(json)?;
+    assert_eq!(product.price_history, products[0].price_history);
+
+    let input = with_price("299.99 UAH\n*Історія цін*: 2024-13-01: 349.99 UAH")?;
+    let (_, errors) = parse_catalog(&input);
+    assert_eq!(
+        errors[0].to_string(),
+        "line 6: invalid price history date `2024-13-01`"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_convert_discount() -> anyhow::Result<()> {
+    let input = with_price(
+        "440 UAH (was 880 UAH, -50%)\n*Price History*: 2024-11-01: 880 UAH; 2025-01-15: 20 EUR",
+    )?;
+    let (mut products, errors) = parse_catalog(&input);
+    assert!(errors.is_empty(), "unexpected errors {:?}", errors);
+
+    let rates = ExchangeRates::from_toml(
+        "[[rates]]\nfrom = \"EUR\"\nto = \"UAH\"\nrate = 44.0\neffective = \"2024-01-01\"\n",
+    )?;
+    let date = NaiveDate::from_ymd_opt(2025, 2, 1).unwrap_or_default();
+    rates.convert_product(&mut products[0], "EUR", date)?;
+
+    let pricing = products[0].pricing();
+    assert_eq!((pricing.current, pricing.original), (10.0, Some(20.0)));
+    assert_eq!(pricing.discount_percent, Some(50.0));
+    let history: Vec<f64> = pricing.history.iter().map(|p| p.price).collect();
+    assert_eq!(history, vec![20.0, 20.0]);
+    assert!(pricing
+        .history
+        .iter()
+        .all(|p| p.currency == Some(Currency::Eur)));
+
+    Ok(())
+}
@@ -0,0 +1,61 @@
+use anyhow::anyhow;
+use cosmetics_parser::product_ref::*;
+use cosmetics_parser::*;
+use std::fs;
+
+#[test]
+fn test_product_ref_fields() -> anyhow::Result<()> {
+    let file_content = fs::read_to_string("src/input.txt")?;
+    let block = *product_blocks(&file_content)
+        .get(1)
+        .ok_or_else(|| anyhow!("no block"))?;
+
+    let product = ProductRef::parse(&block)?;
+
+    assert_eq!(
+        product.product_name,
+        "Sunscreen \"SPF 50+ Ultra Protection\""
+    );
+    assert_eq!(product.skin_type, "All Skin Types");
+    assert_eq!(product.rating, 4.7);
+    assert_eq!(product.price, 349.99);
+    assert_eq!(product.user_ratings, vec![5.0, 5.0, 4.0, 5.0, 5.0]);
+    assert_eq!(product.reviews.len(), 2);
+    assert!(product.availability.is_in_stock());
+
+    // Text fields borrow from the input instead of being copied
+    let input_range = file_content.as_bytes().as_ptr_range();
+    assert!(input_range.contains(&product.product_name.as_ptr()));
+    assert!(input_range.contains(&product.reviews[0].as_ptr()));
+
+    Ok(())
+}
+
+#[test]
+fn test_product_ref_into_product() -> anyhow::Result<()> {
+    let file_content = fs::read_to_string("src/input.txt")?;
+
+    for block in product_blocks(&file_content) {
+        let borrowed = ProductRef::parse(&block)?;
+        let mut owned = parse_product(&block)?;
+        owned.source_map = None;
+
+        assert_eq!(
+            serde_json::to_value(Product::from(borrowed.clone()))?,
+            serde_json::to_value(&owned)?
+        );
+        assert_eq!(
+            serde_json::to_value(&borrowed)?,
+            serde_json::to_value(&owned)?
+        );
+    }
+
+    let product = ProductRef::parse(&ProductBlock {
+        line: 1,
+        offset: 0,
+        text: "*Product 1*: Toner\n*Rating*: 4.1\n",
+    });
+    assert!(product.is_err(), "Expected error but got {:?}", product);
+
+    Ok(())
+}
@@ -0,0 +1,127 @@
+mod common;
+
+use common::first_product_with;
+use cosmetics_parser::currency::Currency;
+use cosmetics_parser::quantity::*;
+use cosmetics_parser::query::*;
+use cosmetics_parser::*;
+use std::fs;
+
+/// The first product of the sample catalog with a size line before its user ratings
+fn with_size(size: &str) -> anyhow::Result<String> {
+    first_product_with("*User Ratings*", &format!("{}\n*User Ratings*", size))
+}
+
+#[test]
+fn test_sizes() -> anyhow::Result<()> {
+    let cases = [
+        ("*Volume*: 50 ml", 50.0, Unit::Milliliter),
+        ("*Size*: 1,5 l", 1.5, Unit::Liter),
+        ("*Size*: 1.7 fl oz", 1.7, Unit::FluidOunce),
+        ("*Size*: 1.7 FL. OZ", 1.7, Unit::FluidOunce),
+        ("*Size*: 200g", 200.0, Unit::Gram),
+        ("*Size*: 0.5 kg", 0.5, Unit::Kilogram),
+        ("*Size*: 3 oz", 3.0, Unit::Ounce),
+        ("*Об'єм*: 200 мл", 200.0, Unit::Milliliter),
+        ("*Size*: 50 ml / 1.7 fl oz", 50.0, Unit::Milliliter),
+        ("*Volume*: 1.7 fl oz/50ml", 1.7, Unit::FluidOunce),
+    ];
+    for (line, amount, unit) in cases {
+        let (products, errors) = parse_catalog(&with_size(line)?);
+        assert!(errors.is_empty(), "{}: {:?}", line, errors);
+        assert_eq!(
+            products[0].size,
+            Some(Quantity { amount, unit }),
+            "{}",
+            line
+        );
+        assert!(products[0].extra.is_empty(), "{}", line);
+    }
+
+    let (_, errors) = parse_catalog(&with_size("*Size*: 50 ml\n*Volume*: 50 ml")?);
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].to_string(), "line 7: size given more than once");
+
+    let (products, errors) = parse_catalog(&with_size("*Size*: Travel size")?);
+    assert!(errors.is_empty(), "{:?}", errors);
+    assert_eq!(products[0].size, None);
+    assert_eq!(
+        products[0].extra.get("Size").map(String::as_str),
+        Some("Travel size")
+    );
+
+    let (products, errors) = parse_catalog(&with_size("*Size*: Travel size\n*Volume*: 15 ml")?);
+    assert!(errors.is_empty(), "{:?}", errors);
+    assert_eq!(
+        products[0].size,
+        Some(Quantity {
+            amount: 15.0,
+            unit: Unit::Milliliter
+        })
+    );
+
+    let (products, _) = parse_catalog(&with_size("*Size*: 1.7 fl oz")?);
+    let json = serde_json::to_value(&products[0])?;
+    assert_eq!(
+        json["size"],
+        serde_json::json!({ "amount": 1.7, "unit": "fl oz" })
+    );
+    let product: Product = serde_json::from_value(json)?;
+    assert_eq!(product.size, products[0].size);
+
+    Ok(())
+}
+
+#[test]
+fn test_unit_price() -> anyhow::Result<()> {
+    let cases = [
+        ("*Size*: 50 ml", Some(599.98), Dimension::Volume),
+        ("*Size*: 1 l", Some(30.0), Dimension::Volume),
+        ("*Size*: 250 g", Some(120.0), Dimension::Mass),
+        ("*Size*: 1 oz", Some(1058.18), Dimension::Mass),
+        ("*Size*: 1 fl oz", Some(1014.39), Dimension::Volume),
+        ("*Size*: 0 ml", None, Dimension::Volume),
+    ];
+    for (line, unit_price, dimension) in cases {
+        let (products, errors) = parse_catalog(&with_size(line)?);
+        assert!(errors.is_empty(), "{}: {:?}", line, errors);
+        assert_eq!(products[0].currency, Some(Currency::Uah));
+        assert_eq!(products[0].unit_price(), unit_price, "{}", line);
+        let size = products[0].size.ok_or_else(|| anyhow::anyhow!("no size"))?;
+        assert_eq!(size.dimension(), dimension, "{}", line);
+    }
+
+    let (products, _) = parse_catalog(&with_size("*Size*: 1.5 kg")?);
+    let size = products[0].size.ok_or_else(|| anyhow::anyhow!("no size"))?;
+    assert_eq!(size.base_amount(), 1500.0);
+    assert_eq!(size.to_string(), "1.5 kg");
+    assert_eq!(size.dimension().unit_price_basis(), "100 g");
+
+    Ok(())
+}
+
+#[test]
+fn test_query_unit_price() -> anyhow::Result<()> {
+    let (mut products, _) = parse_catalog(&with_size("*Size*: 50 ml")?);
+    let (large, _) = parse_catalog(&with_size("*Size*: 1 l")?);
+    let file_content = fs::read_to_string("src/input.txt")?;
+    let (unsized_products, _) = parse_catalog(&file_content);
+    products.extend(large);
+    products.push(unsized_products[1].clone());
+
+    let query = Query::parse("unit_price < 100")?;
+    let matches = query.filter(&products);
+    assert_eq!(matches.len(), 1);
+    assert_eq!(matches[0].unit_price(), Some(30.0));
+
+    for order in [SortOrder::Asc, SortOrder::Desc] {
+        let mut sorted: Vec<&Product> = products.iter().collect();
+        sort_products(&mut sorted, Field::UnitPrice, order);
+        assert_eq!(sorted[2].size, None, "{:?}", order);
+    }
+
+    let projected = project(&products[2], &[Field::UnitPrice]);
+    assert_eq!(projected["unit_price"], serde_json::Value::Null);
+
+    Ok(())
+}
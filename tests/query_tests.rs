@@ -0,0 +1,130 @@
+mod common;
+
+use common::catalog;
+use cosmetics_parser::availability::Availability;
+use cosmetics_parser::query::*;
+use cosmetics_parser::*;
+
+fn names(products: &[&Product]) -> Vec<String> {
+    products.iter().map(|p| p.product_name.clone()).collect()
+}
+
+#[test]
+fn test_query_filter() -> anyhow::Result<()> {
+    let products = catalog()?;
+
+    let query = Query::parse("price < 300 and availability and skin_type ~ \"oily\"")?;
+    assert_eq!(
+        names(&query.filter(&products)),
+        vec!["Face Mask \"Deep Cleansing Clay\""]
+    );
+
+    let query = Query::parse("ingredients contains \"Retinol\"")?;
+    assert_eq!(
+        names(&query.filter(&products)),
+        vec!["Night Serum \"Revitalizing\""]
+    );
+
+    let query = Query::parse("not availability or (rating >= 4.8 && user_ratings contains 4)")?;
+    assert_eq!(query.filter(&products).len(), 2);
+
+    let query = Query::parse("reviews ~ \"WHITE CAST\"")?;
+    assert_eq!(query.filter(&products).len(), 1);
+
+    Ok(())
+}
+
+#[test]
+fn test_query_availability() -> anyhow::Result<()> {
+    let mut products = catalog()?;
+    products[1].availability = Availability::PreOrder { until: None };
+    products[2].availability = Availability::InStock { quantity: Some(12) };
+    products[3].availability = Availability::Discontinued;
+
+    let query = Query::parse("availability = \"pre_order\"")?;
+    assert_eq!(
+        names(&query.filter(&products)),
+        vec![products[1].product_name.clone()]
+    );
+
+    let query = Query::parse("availability != \"in_stock\"")?;
+    assert_eq!(query.filter(&products).len(), 3);
+    assert_eq!(Query::parse("availability")?.filter(&products).len(), 4);
+
+    let query = Query::parse("stock_quantity >= 10")?;
+    assert_eq!(
+        names(&query.filter(&products)),
+        vec![products[2].product_name.clone()]
+    );
+
+    let projected = project(&products[2], &[Field::Availability, Field::StockQuantity]);
+    assert_eq!(
+        projected["availability"],
+        serde_json::json!({ "status": "in_stock", "quantity": 12 })
+    );
+    assert_eq!(projected["stock_quantity"].as_u64(), Some(12));
+    let projected = project(&products[3], &[Field::StockQuantity]);
+    assert_eq!(projected["stock_quantity"], serde_json::Value::Null);
+
+    let error = Query::parse("availability = \"preorder\"");
+    assert!(matches!(error, Err(QueryError::UnknownStatus(_))));
+    assert!(matches!(
+        Query::parse("availability ~ \"pre\""),
+        Err(QueryError::Type { .. })
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_query_errors() {
+    assert!(matches!(
+        Query::parse("prise < 300"),
+        Err(QueryError::UnknownField(_))
+    ));
+    assert!(matches!(
+        Query::parse("price ~ \"300\""),
+        Err(QueryError::Type { .. })
+    ));
+    assert!(matches!(
+        Query::parse("availability > true"),
+        Err(QueryError::Type { .. })
+    ));
+    assert!(matches!(
+        Query::parse("price <"),
+        Err(QueryError::Syntax(_))
+    ));
+    assert!(matches!(Query::parse("and"), Err(QueryError::Syntax(_))));
+}
+
+#[test]
+fn test_sort_and_project() -> anyhow::Result<()> {
+    let products = catalog()?;
+
+    let mut matches = Query::parse("availability")?.filter(&products);
+    sort_products(&mut matches, "rating".parse()?, SortOrder::Desc);
+    let ratings: Vec<f64> = matches.iter().map(|p| p.rating).collect();
+    assert_eq!(ratings, vec![4.8, 4.7, 4.6, 4.4, 4.3, 4.2]);
+
+    let projected = project(matches[0], &[Field::Price, Field::ProductName]);
+    let keys: Vec<&String> = projected.keys().collect();
+    assert_eq!(keys, vec!["price", "product_name"]);
+    assert_eq!(projected["price"], 199.99);
+
+    let projected = project(&products[2], &[Field::ProductId]);
+    assert_eq!(serde_json::to_string(&projected)?, r#"{"product_id":3}"#);
+    let query = Query::parse("product_id >= 3 and product_id < 3.5")?;
+    assert_eq!(
+        names(&query.filter(&products)),
+        vec![products[2].product_name.clone()]
+    );
+
+    let mut products = products;
+    products[0].product_id = None;
+    let query = Query::parse("product_id = 0 or product_id < 1")?;
+    assert!(query.filter(&products).is_empty());
+    let projected = project(&products[0], &[Field::ProductId]);
+    assert_eq!(projected["product_id"], serde_json::Value::Null);
+
+    Ok(())
+}
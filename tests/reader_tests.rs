@@ -0,0 +1,54 @@
+use cosmetics_parser::reader::*;
+use cosmetics_parser::*;
+use std::fs;
+use std::io::{BufReader, Cursor, Read};
+
+#[test]
+fn test_reader_matches_catalog() -> anyhow::Result<()> {
+    let file_content = fs::read_to_string("src/input.txt")?;
+
+    let (expected, _) = parse_catalog(&file_content);
+    let products =
+        ProductReader::new(Cursor::new(file_content.as_bytes())).collect::<Result<Vec<_>, _>>()?;
+
+    assert_eq!(products.len(), 7);
+    assert_eq!(
+        serde_json::to_value(&products)?,
+        serde_json::to_value(&expected)?
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_reader_continues_after_error() -> anyhow::Result<()> {
+    let file_content = fs::read_to_string("src/input.txt")?;
+    let input = format!("\n*Product 0*: Toner\n*Rating*: 4.1\n\n{}", file_content);
+
+    // A tiny buffer makes sure blocks are assembled across many reads
+    let reader = BufReader::with_capacity(8, Cursor::new(input.into_bytes()));
+    let results: Vec<_> = ProductReader::new(reader).collect();
+
+    assert_eq!(results.len(), 8);
+    match &results[0] {
+        Err(e) => assert!(e.to_string().starts_with("line 3:"), "got {}", e),
+        Ok(_) => panic!("expected an error for the broken block"),
+    }
+    assert!(results[1..].iter().all(|r| r.is_ok()));
+
+    Ok(())
+}
+
+#[test]
+fn test_reader_reports_io_errors() {
+    struct Failing;
+    impl Read for Failing {
+        fn read(&mut self, _: &mut [u8]) -> std::io::Result<usize> {
+            Err(std::io::Error::other("pipe closed"))
+        }
+    }
+
+    let mut reader = ProductReader::new(BufReader::new(Failing));
+    assert!(matches!(reader.next(), Some(Err(ParseError::Io(_)))));
+    assert!(reader.next().is_none());
+}
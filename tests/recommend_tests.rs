@@ -0,0 +1,106 @@
+mod common;
+
+use common::catalog;
+use cosmetics_parser::recommend::*;
+
+fn ids(recommendations: &[Recommendation]) -> Vec<Option<u64>> {
+    recommendations.iter().map(|r| r.product_id).collect()
+}
+
+#[test]
+fn test_skin_match() -> anyhow::Result<()> {
+    let cases = [
+        ("Dry Skin", "dry", 1.0),
+        ("Normal to Dry Skin", "Dry Skin", 1.0),
+        ("Oily/Combination Skin", "combination", 1.0),
+        ("All Skin Types", "oily", 0.8),
+        ("Mature Skin", "dry", 0.0),
+        ("Oily Skin", "", 0.0),
+    ];
+    for (product, wanted, score) in cases {
+        assert_eq!(
+            skin_match(product, wanted),
+            score,
+            "{} / {}",
+            product,
+            wanted
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_recommend() -> anyhow::Result<()> {
+    let products = catalog()?;
+    let recommendations = recommend(&products, &Preferences::new("dry"));
+    assert_eq!(
+        ids(&recommendations),
+        vec![Some(4), Some(6), Some(1), Some(2)]
+    );
+
+    let best = &recommendations[0];
+    assert_eq!(best.score, 0.902);
+    assert_eq!(
+        best.breakdown,
+        ScoreBreakdown {
+            skin_type: 0.4,
+            rating: 0.288,
+            user_ratings: 0.15,
+            price: 0.064,
+        }
+    );
+    assert_eq!(
+        best.to_string(),
+        "0.902  Body Lotion \"Silk Touch\" (Normal to Dry Skin, 199.99 UAH): \
+         skin type 0.400 + rating 0.288 + user ratings 0.150 + price 0.064"
+    );
+    let json = serde_json::to_value(best)?;
+    assert_eq!(json["breakdown"]["rating"], 0.288);
+
+    let oily = recommend(&products, &Preferences::new("oily"));
+    assert_eq!(oily[0].skin_type, "Oily Skin");
+    assert_eq!(oily[1].skin_type, "All Skin Types");
+    assert!(oily[0].price > oily[1].price);
+
+    assert!(recommend(&products, &Preferences::new("sensitive"))
+        .iter()
+        .all(|r| r.skin_type == "All Skin Types"));
+
+    Ok(())
+}
+
+#[test]
+fn test_constraints() -> anyhow::Result<()> {
+    let products = catalog()?;
+
+    let mut preferences = Preferences::new("dry");
+    preferences.in_stock = true;
+    assert_eq!(
+        ids(&recommend(&products, &preferences)),
+        vec![Some(4), Some(6), Some(2)]
+    );
+
+    preferences.budget = Some(200.0);
+    assert_eq!(
+        ids(&recommend(&products, &preferences)),
+        vec![Some(4), Some(6)]
+    );
+
+    preferences.exclude = vec![String::from("coconut oil")];
+    let recommendations = recommend(&products, &preferences);
+    assert!(recommendations.is_empty(), "{:?}", recommendations);
+
+    let mut preferences = Preferences::new("oily");
+    preferences.weights = Weights {
+        skin_type: 0.0,
+        rating: 0.0,
+        user_ratings: 0.0,
+        price: 1.0,
+    };
+    let recommendations = recommend(&products, &preferences);
+    assert_eq!(recommendations[0].product_id, Some(6));
+    assert_eq!(recommendations[0].score, 0.9);
+
+    Ok(())
+}
@@ -0,0 +1,104 @@
+use cosmetics_parser::merge::{merge_catalogs, ConflictPolicy};
+use cosmetics_parser::reviews::*;
+use cosmetics_parser::*;
+
+const CATALOG: &str = r#"*Product 1*: Face Cream "Moisturizing"
+*Skin Type*: Dry Skin
+*Ingredients*: Water, Glycerin
+*Rating*: 4.5
+*Price*: 299.99 UAH
+*User Ratings*: [5, 4, 5]
+*Recommendations*: Use daily.
+*Reviews*:
+1. (5/5) "Lovely"
+2. (2/5) "Greasy"
+3. "No stars here"
+*Availability*: true
+
+*Product 2*: Toner "Fresh"
+*Skin Type*: Oily Skin
+*Ingredients*: Water
+*Rating*: 4.0
+*Price*: 100 UAH
+*User Ratings*: [4, 5]
+*Recommendations*: Use daily.
+*Reviews*:
+1. (8/10) "Nice"
+*Availability*: true
+
+"#;
+
+#[test]
+fn test_parse_review_stars() -> anyhow::Result<()> {
+    let (products, errors) = parse_catalog(CATALOG);
+    assert!(errors.is_empty(), "{:?}", errors);
+
+    assert_eq!(products[0].reviews.len(), 3);
+    assert_eq!(products[0].reviews[0], "1. (5/5) \"Lovely\"");
+    let reviews: Vec<(usize, f64)> = products[0]
+        .review_stars
+        .iter()
+        .map(|s| (s.review, s.stars))
+        .collect();
+    assert_eq!(reviews, vec![(0, 5.0), (1, 2.0)]);
+    assert_eq!(products[1].review_stars[0].out_of_five(), 4.0);
+
+    let (products, errors) = parse_catalog(&CATALOG.replace("(8/10) \"Nice\"", "(4/5)"));
+    assert!(errors.is_empty(), "{:?}", errors);
+    assert_eq!(products[1].reviews, vec!["1. (4/5)"]);
+    assert_eq!(products[1].review_stars[0].stars, 4.0);
+
+    let (products, errors) = parse_catalog(&CATALOG.replace("(8/10)", "(12/10)"));
+    assert!(errors.is_empty(), "{:?}", errors);
+    assert_eq!(products.len(), 2);
+    assert_eq!(products[1].reviews, vec!["1. (12/10) \"Nice\""]);
+    assert!(products[1].review_stars.is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_report_disagreements() -> anyhow::Result<()> {
+    let (mut products, _) = parse_catalog(CATALOG);
+
+    let check = check_stars(&products[0]).ok_or_else(|| anyhow::anyhow!("no check"))?;
+    assert_eq!(check.review_average, 3.5);
+    assert_eq!(check.user_average, Some(4.67));
+    assert_eq!(check.unmatched.len(), 1);
+    assert_eq!(check.unmatched[0].review, 1);
+    assert!(check.disagrees());
+
+    let report = report(&products);
+    assert_eq!(report.checked, 2);
+    assert_eq!(report.products.len(), 1);
+    assert!(report
+        .to_string()
+        .contains("review 2 gives 2/5 stars, which no user rating matches"));
+
+    products[1].user_ratings.clear();
+    let check = check_stars(&products[1]).ok_or_else(|| anyhow::anyhow!("no check"))?;
+    assert_eq!(check.user_average, None);
+    assert!(check.disagrees());
+
+    products[1].review_stars.clear();
+    assert_eq!(check_stars(&products[1]), None);
+    Ok(())
+}
+
+#[test]
+fn test_union_keeps_review_stars() -> anyhow::Result<()> {
+    let (first, _) = parse_catalog(CATALOG);
+    let mut second = vec![first[0].clone()];
+    second[0].reviews = vec![
+        String::from("1. (5/5) \"Lovely\""),
+        String::from("2. (4/5) \"Works well\""),
+    ];
+    second[0].review_stars[1].stars = 4.0;
+
+    let report = merge_catalogs(&[first, second], ConflictPolicy::Union)?;
+    let product = &report.products[0];
+    assert_eq!(product.reviews.len(), 4);
+    let last = product.review_stars.last().map(|s| (s.review, s.stars));
+    assert_eq!(last, Some((3, 4.0)));
+    assert!(report.conflicts.iter().all(|c| c.field != "review_stars"));
+    Ok(())
+}
@@ -0,0 +1,87 @@
+use cosmetics_parser::catalog::*;
+use cosmetics_parser::search::*;
+use cosmetics_parser::*;
+use std::fs;
+
+fn catalog() -> anyhow::Result<Catalog> {
+    let file_content = fs::read_to_string("src/input.txt")?;
+    let (catalog, errors) = Catalog::parse(&file_content, &ParseOptions::default());
+    assert!(errors.is_empty(), "unexpected errors {:?}", errors);
+    Ok(catalog)
+}
+
+fn names(hits: &[SearchHit]) -> Vec<String> {
+    hits.iter()
+        .map(|h| h.product.product_name.clone())
+        .collect()
+}
+
+#[test]
+fn test_tokenize() -> anyhow::Result<()> {
+    assert_eq!(fold("Crème BRÛLÉE Straße"), "creme brulee strasse");
+    assert_eq!(fold("Їжак"), "іжак");
+    assert_eq!(
+        tokenize("SPF 50+ Ultra-Protection, (Oily/Combination)"),
+        vec!["spf", "50", "ultra", "protection", "oily", "combination"]
+    );
+    assert!(tokenize(" ,.- ").is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn test_search() -> anyhow::Result<()> {
+    let catalog = catalog()?;
+    assert_eq!(catalog.products().len(), 7);
+
+    let hits = catalog.search("hydrating oily");
+    assert_eq!(
+        names(&hits),
+        vec![
+            "Eye Cream \"Anti-Aging\"",
+            "Night Serum \"Revitalizing\"",
+            "Face Mask \"Deep Cleansing Clay\""
+        ]
+    );
+    assert!(hits.windows(2).all(|pair| pair[0].score >= pair[1].score));
+
+    // A rarer word scores higher than a common one, and a name match higher than a review match
+    let retinol = catalog.search("retinol")[0].score;
+    let skin = catalog.search("skin");
+    assert!(retinol > skin[0].score, "{} {:?}", retinol, skin[0].score);
+    assert_eq!(
+        catalog.search("serum")[0].product.product_name,
+        "Night Serum \"Revitalizing\""
+    );
+
+    assert!(catalog.search("").is_empty());
+    assert!(catalog.search("sunburnt shampoo").is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn test_prefix_and_folding() -> anyhow::Result<()> {
+    let catalog = catalog()?;
+
+    let hits = catalog.search("HYDRAT");
+    assert_eq!(
+        names(&hits),
+        vec!["Eye Cream \"Anti-Aging\"", "Body Lotion \"Silk Touch\""]
+    );
+    assert_eq!(
+        names(&catalog.search("Nïacinamidé")),
+        names(&catalog.search("niacinamide"))
+    );
+
+    // Short words only match whole words
+    assert!(catalog.search("hy").is_empty());
+    let exact = catalog.search("hydrating")[0].score;
+    let prefix = catalog.search("hydrati")[0].score;
+    assert!(exact > prefix, "{} {}", exact, prefix);
+
+    let json = serde_json::to_value(&hits[0])?;
+    assert_eq!(json["product"]["product_id"], 5);
+
+    Ok(())
+}
@@ -0,0 +1,93 @@
+use cosmetics_parser::config;
+use cosmetics_parser::sentiment::*;
+use cosmetics_parser::stats::CatalogStats;
+use cosmetics_parser::*;
+use std::fs;
+
+fn keywords(sentiment: &ReviewSentiment) -> Vec<&str> {
+    sentiment
+        .mentions
+        .iter()
+        .map(|m| m.keyword.as_str())
+        .collect()
+}
+
+#[test]
+fn test_analyze_reviews() -> anyhow::Result<()> {
+    let lexicon = Lexicon::builtin();
+
+    let review = lexicon.analyze("Feels light on the skin and doesn’t leave a white cast.");
+    assert_eq!(keywords(&review), vec!["light", "no white cast"]);
+    assert_eq!(review.score, 1.0);
+
+    let review = lexicon.analyze("My pores look visibly smaller after each use!");
+    assert_eq!(keywords(&review), vec!["smaller pores"]);
+
+    let review = lexicon.analyze("Not greasy, but it stings and left a white cast.");
+    assert_eq!(
+        keywords(&review),
+        vec!["not greasy", "irritation", "white cast"]
+    );
+    assert!(review.score < 0.0);
+
+    let review = lexicon.analyze("Arrived on time.");
+    assert!(review.mentions.is_empty());
+    assert_eq!(review.score, 0.0);
+    Ok(())
+}
+
+#[test]
+fn test_contradicting_rating() -> anyhow::Result<()> {
+    let file_content = fs::read_to_string("src/input.txt")?;
+    let (mut products, _) = parse_catalog(&file_content);
+    let lexicon = Lexicon::builtin();
+
+    let summary = lexicon
+        .summarize(&products[3])
+        .ok_or_else(|| anyhow::anyhow!("no summary"))?;
+    assert_eq!(summary.positive, 2);
+    assert!(!summary.contradicts_rating);
+    assert!(summary.keywords.iter().any(|k| k.keyword == "not greasy"));
+
+    products[3].reviews = vec![
+        String::from("1. \"Greasy and it broke me out.\""),
+        String::from("2. \"Terrible, my skin was irritated for days.\""),
+    ];
+    lexicon.annotate(&mut products);
+    let sentiment = products[3].sentiment.as_ref();
+    assert_eq!(sentiment.map(|s| s.score), Some(-1.0));
+    assert_eq!(sentiment.map(|s| s.contradicts_rating), Some(true));
+    assert_eq!(sentiment.map(|s| s.expected_rating()), Some(1.0));
+
+    products[3].reviews.clear();
+    assert_eq!(lexicon.summarize(&products[3]), None);
+    Ok(())
+}
+
+#[test]
+fn test_custom_lexicon_in_stats() -> anyhow::Result<()> {
+    let file_content = fs::read_to_string("src/input.txt")?;
+    let (products, _) = parse_catalog(&file_content);
+
+    let stats = CatalogStats::new(&products, 3);
+    assert_eq!(stats.review_sentiment.len(), products.len());
+    assert_eq!(stats.top_keywords.len(), 3);
+    assert!(stats
+        .review_sentiment
+        .iter()
+        .all(|p| !p.sentiment.contradicts_rating));
+
+    let lexicon = config::from_toml::<Lexicon>(
+        r#"
+[[terms]]
+term = "white cast"
+score = -1.0
+negated = "no white cast"
+"#,
+    )?;
+    let stats = CatalogStats::with_lexicon(&products, 10, &lexicon);
+    let keywords: Vec<&str> = stats.top_keywords.iter().map(|c| c.name.as_str()).collect();
+    assert_eq!(keywords, vec!["no white cast"]);
+    assert!(config::from_json::<Lexicon>("{\"terms\": 1}").is_err());
+    Ok(())
+}
@@ -0,0 +1,83 @@
+use anyhow::anyhow;
+use cosmetics_parser::reader::ProductReader;
+use cosmetics_parser::source_map::*;
+use cosmetics_parser::*;
+use pest::Parser;
+use std::fs;
+use std::io::Cursor;
+
+#[test]
+fn test_source_map_fields() -> anyhow::Result<()> {
+    let file_content = fs::read_to_string("src/input.txt")?;
+
+    let (products, _) = parse_catalog(&file_content);
+    let product = products.get(1).ok_or_else(|| anyhow!("no product"))?;
+    let source_map = product
+        .source_map
+        .as_ref()
+        .ok_or_else(|| anyhow!("no source map"))?;
+
+    assert_eq!(source_map.product.line, 12);
+
+    let price = source_map
+        .field("price")
+        .ok_or_else(|| anyhow!("no price span"))?;
+    assert_eq!(&file_content[price.start..price.end], "349.99");
+    assert_eq!((price.line, price.column), (16, 10));
+    assert_eq!(price.to_string(), "16:10");
+
+    let name = source_map
+        .field("product_name")
+        .ok_or_else(|| anyhow!("no name span"))?;
+    assert_eq!(&file_content[name.start..name.end], product.product_name);
+
+    assert_eq!(source_map.reviews.len(), 2);
+    let review = source_map.reviews[1];
+    assert_eq!(&file_content[review.start..review.end], product.reviews[1]);
+    assert_eq!((review.line, review.column), (21, 1));
+
+    Ok(())
+}
+
+#[test]
+fn test_source_map_matches_across_parsers() -> anyhow::Result<()> {
+    let file_content = fs::read_to_string("src/input.txt")?;
+
+    let pairs = Grammar::parse(Rule::products, &file_content)?
+        .next()
+        .ok_or_else(|| anyhow!("no pair"))?;
+    let from_pairs: Vec<SourceMap> = pairs
+        .into_inner()
+        .map(|pair| SourceMap::from_pair(&pair))
+        .collect();
+
+    let (products, _) = parse_catalog(&file_content);
+    let from_blocks: Vec<SourceMap> = products.into_iter().flat_map(|p| p.source_map).collect();
+
+    let from_reader: Vec<SourceMap> = ProductReader::new(Cursor::new(file_content.as_bytes()))
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter()
+        .flat_map(|p| p.source_map)
+        .collect();
+
+    assert_eq!(from_pairs.len(), 7);
+    assert_eq!(from_pairs, from_blocks);
+    assert_eq!(from_pairs, from_reader);
+
+    Ok(())
+}
+
+#[test]
+fn test_source_map_in_json() -> anyhow::Result<()> {
+    let file_content = fs::read_to_string("src/input.txt")?;
+
+    let (mut products, _) = parse_catalog(&file_content);
+    let json = serde_json::to_value(&products[0])?;
+    assert_eq!(json["source_map"]["fields"]["rating"]["line"], 4);
+
+    products[0].source_map = None;
+    let json = serde_json::to_value(&products[0])?;
+    assert!(json.get("source_map").is_none());
+
+    Ok(())
+}
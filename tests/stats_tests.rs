@@ -0,0 +1,73 @@
+use cosmetics_parser::stats::*;
+use cosmetics_parser::*;
+use std::fs;
+
+#[test]
+fn test_catalog_stats() -> anyhow::Result<()> {
+    let file_content = fs::read_to_string("src/input.txt")?;
+    let (products, _) = parse_catalog(&file_content);
+
+    let stats = CatalogStats::new(&products, 3);
+
+    assert_eq!(stats.products, 7);
+    assert_eq!((stats.in_stock, stats.out_of_stock), (6, 1));
+
+    let uah = &stats.prices["UAH"];
+    assert_eq!(uah.count, 7);
+    assert_eq!((uah.min, uah.max, uah.median), (59.99, 599.99, 299.99));
+    assert!((uah.mean - 315.7057).abs() < 1e-3);
+
+    assert_eq!(stats.rating_distribution[0].name, "4.0-4.5");
+    assert_eq!(stats.rating_distribution[0].count, 3);
+    assert_eq!(stats.user_ratings.total, 35);
+    assert_eq!(stats.reviews.total, 13);
+
+    assert_eq!(stats.top_ingredients.len(), 3);
+    assert_eq!(stats.top_ingredients[0].name, "Aloe Vera");
+    assert_eq!(stats.top_ingredients[0].count, 3);
+    assert_eq!(stats.skin_types[0].name, "All Skin Types");
+    assert_eq!(stats.skin_types[0].count, 2);
+
+    Ok(())
+}
+
+#[test]
+fn test_stats_per_currency() -> anyhow::Result<()> {
+    let input = "*Product 1*: Toner
+*Skin Type*: Dry Skin
+*Ingredients*: Water
+*Rating*: 4.0
+*Price*: 10 EUR
+*User Ratings*: [4]
+*Recommendations*: Daily.
+*Reviews*:
+1. \"Nice\"
+*Availability*: false
+
+*Product 2*: Mist
+*Skin Type*: dry skin
+*Ingredients*: water, Rose
+*Rating*: 5
+*Price*: 20
+*User Ratings*: [5, 5]
+*Recommendations*: Daily.
+*Reviews*:
+1. \"Fresh\"
+*Availability*: true
+";
+    let (products, errors) = parse_catalog(input);
+    assert!(errors.is_empty(), "unexpected errors {:?}", errors);
+
+    let stats = CatalogStats::new(&products, 10);
+    assert_eq!(stats.prices["EUR"].mean, 10.0);
+    assert_eq!(stats.prices["-"].mean, 20.0);
+    assert_eq!(stats.rating_distribution[1].name, "4.5-5.0");
+    assert_eq!(stats.skin_types.len(), 1);
+    assert_eq!(stats.top_ingredients[0].count, 2);
+    assert_eq!(stats.reviews.average_per_product, 1.0);
+
+    let table = stats.to_string();
+    assert!(table.contains("Out of stock         1"), "got {}", table);
+
+    Ok(())
+}